@@ -0,0 +1,113 @@
+use crate::ffi;
+use crate::{copy_string_list, GdError};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_int;
+
+/// Aliases, alternative field codes for a field
+///
+/// Most functions follow aliases to their target, eg. `get_entry` on an alias returns the
+/// entry of the target field. The functions here work on the aliases themselves.
+impl crate::Dirfile {
+    /// number of field codes which refer to `field_code`, including itself
+    pub fn naliases(&self, field_code: &str) -> Result<usize, GdError> {
+        debug_call!(self, "naliases", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let n = unsafe { ffi::gd_naliases(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if n == 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(n as usize)
+    }
+
+    /// every field code which refers to `field_code`, including itself
+    pub fn aliases(&self, field_code: &str) -> Result<Vec<String>, GdError> {
+        debug_call!(self, "aliases", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let list = unsafe { ffi::gd_aliases(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if list.is_null() {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(unsafe { copy_string_list(list) })
+    }
+
+    /// the field code an alias points to, fails if `alias_name` is not an alias
+    pub fn alias_target(&self, alias_name: &str) -> Result<String, GdError> {
+        debug_call!(self, "alias_target", alias_name);
+        let alias_name = CString::new(alias_name).unwrap();
+        let target = unsafe { ffi::gd_alias_target(self.dirfile.as_ptr(), alias_name.as_ptr()) };
+        if target.is_null() {
+            return Err(self.last_error().unwrap());
+        }
+        //the string belongs to the library
+        Ok(unsafe { CStr::from_ptr(target) }
+            .to_string_lossy()
+            .into_owned())
+    }
+
+    /// removes an alias, its target is left alone
+    pub fn delete_alias(&mut self, alias_name: &str) -> Result<(), GdError> {
+        debug_call!(self, "delete_alias", alias_name);
+        //make sure a field is never deleted by mistake
+        self.alias_target(alias_name)?;
+        let alias_name = CString::new(alias_name).unwrap();
+        let ret_val = unsafe { ffi::gd_delete(self.dirfile.as_ptr(), alias_name.as_ptr(), 0) };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    /// points an existing alias at `target`, the alias stays in the same fragment and
+    /// keeps its `/HIDDEN` flag
+    ///
+    /// If the alias can't be added back with the new target it is restored with the old
+    /// one, so a failure leaves the dirfile as it was. Should even that fail the alias is
+    /// gone, which is reported as `GdError::InternalError`.
+    pub fn retarget_alias(&mut self, alias_name: &str, target: &str) -> Result<(), GdError> {
+        debug_call!(self, "retarget_alias", alias_name, target);
+        let alias_name_c = CString::new(alias_name).unwrap();
+        let target = CString::new(target).unwrap();
+        let old_target = CString::new(self.alias_target(alias_name)?).unwrap();
+        let fragment_index = self.fragment_index(alias_name)?;
+        let hidden = self.hidden(alias_name)?;
+        self.delete_alias(alias_name)?;
+        if let Err(error) = self._add_alias(&alias_name_c, &target, fragment_index, hidden) {
+            if let Err(restore_error) =
+                self._add_alias(&alias_name_c, &old_target, fragment_index, hidden)
+            {
+                return Err(GdError::InternalError(format!(
+                    "Alias {} lost: retargeting failed ({}) and restoring it failed ({})",
+                    alias_name,
+                    error.message(),
+                    restore_error.message()
+                )));
+            }
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    fn _add_alias(
+        &mut self,
+        alias_name: &CString,
+        target: &CString,
+        fragment_index: usize,
+        hidden: bool,
+    ) -> Result<(), GdError> {
+        let ret_val = unsafe {
+            ffi::gd_add_alias(
+                self.dirfile.as_ptr(),
+                alias_name.as_ptr(),
+                target.as_ptr(),
+                fragment_index as c_int,
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        if hidden {
+            self.hide(alias_name.to_str().unwrap())?;
+        }
+        Ok(())
+    }
+}
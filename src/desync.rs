@@ -0,0 +1,79 @@
+use crate::ffi;
+use crate::{Entry, GdError};
+use std::os::raw::c_uint;
+
+/// Options for `Dirfile::desync`, eg. `DesyncOptions::new().reopen(true)`.
+///
+/// By default the format files are only checked, not reloaded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DesyncOptions {
+    reopen: bool,
+    pathcheck: bool,
+}
+
+impl DesyncOptions {
+    pub fn new() -> DesyncOptions {
+        DesyncOptions::default()
+    }
+    /// reload the metadata if it changed on disk (`GD_DESYNC_REOPEN`)
+    pub fn reopen(mut self, reopen: bool) -> DesyncOptions {
+        self.reopen = reopen;
+        self
+    }
+    /// compare the format files by path instead of by open file descriptor, which also
+    /// catches a fragment replaced by a new file or a moved directory
+    /// (`GD_DESYNC_PATHCHECK`)
+    pub fn pathcheck(mut self, pathcheck: bool) -> DesyncOptions {
+        self.pathcheck = pathcheck;
+        self
+    }
+    fn flags_c(&self) -> c_uint {
+        let mut flags = 0;
+        if self.reopen {
+            flags |= ffi::GD_DESYNC_REOPEN as c_uint;
+        }
+        if self.pathcheck {
+            flags |= ffi::GD_DESYNC_PATHCHECK as c_uint;
+        }
+        flags
+    }
+}
+
+/// Following changes made to the metadata by other processes
+impl crate::Dirfile {
+    /// whether the format files changed on disk since the dirfile was opened (or last
+    /// reloaded)
+    ///
+    /// With `DesyncOptions::reopen` a changed dirfile is reloaded, which discards any
+    /// unflushed metadata changes made through this `Dirfile` and bumps `generation`.
+    /// New data appended to existing fields does not need a reload.
+    pub fn desync(&mut self, options: &DesyncOptions) -> Result<bool, GdError> {
+        debug_call!(self, "desync");
+        let ret_val = unsafe { ffi::gd_desync(self.dirfile.as_ptr(), options.flags_c()) };
+        if ret_val < 0 {
+            return Err(self.last_error().unwrap());
+        }
+        let changed = ret_val > 0;
+        if changed && options.reopen {
+            self.generation += 1;
+        }
+        Ok(changed)
+    }
+
+    /// number of times the metadata has been reloaded by `desync`, entries read before
+    /// the last reload may be out of date
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// reads `entries` again from the dirfile, eg. after a reload
+    ///
+    /// Entries which no longer exist (or were renamed) are an error.
+    pub fn refresh_entries(&self, entries: &mut [Entry]) -> Result<(), GdError> {
+        debug_call!(self, "refresh_entries");
+        for entry in entries.iter_mut() {
+            *entry = self.get_entry(entry.get_field_code())?;
+        }
+        Ok(())
+    }
+}
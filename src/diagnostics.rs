@@ -0,0 +1,78 @@
+//! Reporting `Dirfile` calls and GetData errors through `log` and/or `tracing`
+//!
+//! Both are opt-in (features `log` and `tracing`), without them the macros here expand to
+//! nothing. GetData's own `GD_VERBOSE` output goes straight to stderr and can't be
+//! redirected, leave it off and use these instead: every error reported by GetData is
+//! emitted as a warning with the dirfile path, and every call as a debug event with its
+//! arguments (field codes, frames, fragments...).
+
+/// debug event for a `Dirfile` call, eg. `debug_call!(self, "getdata", field_code)` or
+/// `debug_call!(self, "add", field_code = entry.get_field_code())`
+macro_rules! debug_call {
+    ($dirfile:expr, $call:literal $(, $key:ident $(= $value:expr)?)*) => {
+        #[cfg(any(feature = "log", feature = "tracing"))]
+        if let Some(name) = $dirfile.name() {
+            #[cfg(feature = "log")]
+            log::debug!(
+                target: "rust_getdata",
+                concat!($call, " on {}" $(, ", ", stringify!($key), " = {:?}")*),
+                name $(, debug_call!(@value $key $(= $value)?))*
+            );
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                target: "rust_getdata",
+                dirfile = %name,
+                $($key = ?debug_call!(@value $key $(= $value)?),)*
+                $call
+            );
+        }
+    };
+    (@value $key:ident) => {
+        $key
+    };
+    (@value $key:ident = $value:expr) => {
+        $value
+    };
+}
+
+/// warning for an error reported by GetData
+macro_rules! report_error {
+    ($dirfile:expr, $error:expr) => {
+        #[cfg(any(feature = "log", feature = "tracing"))]
+        if let Some(name) = $dirfile.name() {
+            #[cfg(feature = "log")]
+            log::warn!(
+                target: "rust_getdata",
+                "{} on {}: {:?}",
+                $error,
+                name,
+                $error
+            );
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                target: "rust_getdata",
+                dirfile = %name,
+                error = ?$error,
+                "{}",
+                $error
+            );
+        }
+    };
+}
+
+#[cfg(any(feature = "log", feature = "tracing"))]
+impl crate::Dirfile {
+    /// path of the dirfile for the reports, `None` for the scratch dirfiles of the crate
+    /// itself which are not reported
+    ///
+    /// Kept from `open`, asking GetData for it would clear the error `get_error` is
+    /// about to report.
+    pub(crate) fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// stops reporting the calls on a dirfile the crate uses internally
+    pub(crate) fn quiet(&mut self) {
+        self.name = None;
+    }
+}
@@ -0,0 +1,180 @@
+use crate::ffi;
+use crate::Fragment;
+use crate::GdError;
+use std::ffi::CStr;
+use std::os::raw::{c_int, c_ulong};
+
+/// Encodings GetData can store RAW data in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// unencoded binary files
+    None,
+    Text,
+    Gzip,
+    Bzip2,
+    Lzma,
+    Slim,
+    Zzip,
+    Zzslim,
+    Flac,
+    Sie,
+    /// an encoding the linked GetData (or this crate) does not know about, eg. from an
+    /// `/ENCODING` directive written by a newer version
+    Unknown,
+}
+
+impl From<Encoding> for c_ulong {
+    fn from(encoding: Encoding) -> Self {
+        let encoding = match encoding {
+            Encoding::None => ffi::GD_UNENCODED,
+            Encoding::Text => ffi::GD_TEXT_ENCODED,
+            Encoding::Gzip => ffi::GD_GZIP_ENCODED,
+            Encoding::Bzip2 => ffi::GD_BZIP2_ENCODED,
+            Encoding::Lzma => ffi::GD_LZMA_ENCODED,
+            Encoding::Slim => ffi::GD_SLIM_ENCODED,
+            Encoding::Zzip => ffi::GD_ZZIP_ENCODED,
+            Encoding::Zzslim => ffi::GD_ZZSLIM_ENCODED,
+            Encoding::Flac => ffi::GD_FLAC_ENCODED,
+            Encoding::Sie => ffi::GD_SIE_ENCODED,
+            Encoding::Unknown => ffi::GD_ENC_UNSUPPORTED,
+        };
+        encoding as c_ulong
+    }
+}
+
+impl From<c_ulong> for Encoding {
+    fn from(encoding: c_ulong) -> Self {
+        [
+            Encoding::None,
+            Encoding::Text,
+            Encoding::Gzip,
+            Encoding::Bzip2,
+            Encoding::Lzma,
+            Encoding::Slim,
+            Encoding::Zzip,
+            Encoding::Zzslim,
+            Encoding::Flac,
+            Encoding::Sie,
+        ]
+        .into_iter()
+        .find(|known| c_ulong::from(*known) == encoding)
+        .unwrap_or(Encoding::Unknown)
+    }
+}
+
+/// What the linked GetData library can do with an encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingSupport {
+    ReadWrite,
+    ReadOnly,
+    /// the library was built without it
+    Unsupported,
+}
+
+impl Encoding {
+    /// whether this build of GetData can read and write the encoding
+    pub fn support(self) -> EncodingSupport {
+        let support = unsafe { ffi::gd_encoding_support(self.into()) };
+        if support == ffi::GD_RDWR as c_int {
+            EncodingSupport::ReadWrite
+        } else if support == ffi::GD_RDONLY as c_int {
+            EncodingSupport::ReadOnly
+        } else {
+            EncodingSupport::Unsupported
+        }
+    }
+}
+
+/// Encoding of the RAW data of fragments
+impl crate::Dirfile {
+    /// `last_error` after a data access to `field_code` failed, an `UnsupportedEncoding`
+    /// gets the encoding of the fragment the field is defined in
+    ///
+    /// For a derived field that is a best guess, the input which failed may be defined
+    /// in another fragment.
+    pub(crate) fn field_error(&self, field_code: &CStr) -> Option<GdError> {
+        let error = match self.get_error()? {
+            GdError::UnsupportedEncoding(Encoding::Unknown, msg) => {
+                let dirfile = self.dirfile.as_ptr();
+                let fragment_index = unsafe { ffi::gd_fragment_index(dirfile, field_code.as_ptr()) };
+                let encoding = if fragment_index < 0 {
+                    Encoding::Unknown
+                } else {
+                    Encoding::from(unsafe { ffi::gd_encoding(dirfile, fragment_index) })
+                };
+                GdError::UnsupportedEncoding(encoding, msg)
+            }
+            error => error,
+        };
+        report_error!(self, error);
+        Some(error)
+    }
+
+    /// the encoding of a fragment, `Encoding::Unknown` if GetData does not recognise it
+    pub fn encoding(&self, fragment_index: usize) -> Result<Encoding, GdError> {
+        debug_call!(self, "encoding", fragment_index);
+        let encoding = unsafe { ffi::gd_encoding(self.dirfile.as_ptr(), fragment_index as c_int) };
+        if encoding == ffi::GD_AUTO_ENCODED as c_ulong {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(Encoding::from(encoding))
+    }
+
+    /// changes the encoding of one or all fragments
+    ///
+    /// With `recode` the existing RAW files are converted to the new encoding, otherwise
+    /// only the metadata changes and the files are left as they are (use this after
+    /// compressing the files by hand, which works for any known encoding even if this
+    /// build of GetData can't read it). Recoding to an encoding this build can't write, or
+    /// setting `Encoding::Unknown`, fails with `GdError::UnsupportedEncoding` before
+    /// anything is changed.
+    pub fn alter_encoding(
+        &mut self,
+        encoding: Encoding,
+        fragment: impl Into<Fragment>,
+        recode: bool,
+    ) -> Result<(), GdError> {
+        let fragment: Fragment = fragment.into();
+        debug_call!(self, "alter_encoding", encoding, fragment, recode);
+        let unsupported = if recode {
+            encoding.support() != EncodingSupport::ReadWrite
+        } else {
+            encoding == Encoding::Unknown
+        };
+        if unsupported {
+            return Err(GdError::UnsupportedEncoding(
+                encoding,
+                format!("Encoding not supported by this build of GetData: {:?}", encoding),
+            ));
+        }
+        let ret_val = unsafe {
+            ffi::gd_alter_encoding(
+                self.dirfile.as_ptr(),
+                encoding.into(),
+                fragment.into(),
+                recode as c_int,
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+}
+
+/// Byte order of RAW data on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Big,
+    Little,
+}
+
+impl From<Endianness> for c_ulong {
+    fn from(endianness: Endianness) -> Self {
+        let endianness = match endianness {
+            Endianness::Big => ffi::GD_BIG_ENDIAN,
+            Endianness::Little => ffi::GD_LITTLE_ENDIAN,
+        };
+        endianness as c_ulong
+    }
+}
@@ -1,190 +1,848 @@
 use crate::ffi;
-use std::ffi::CString;
+use crate::{GdError, GdTypes};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_uint};
+
 pub struct Entry {
     pub field: CString,
     pub field_type: EntryType,
-    // flags: None, //non implemented
-    pub entry_c: ffi::gd_entry_t,
+    fragment_index: usize,
+    hidden: bool,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum EntryType {
+    No,
+    Bit(BitData),
+    Carray(CarrayData),
+    Const(ConstData),
+    Divide(BinaryData),
+    Indir(BinaryData),
+    Lincom(LincomData),
+    Linterp(LinterpData),
+    Mplex(MplexData),
+    Multiply(BinaryData),
+    Phase(PhaseData),
+    Polynom(PolynomData),
+    Raw(RawData),
+    Recip(RecipData),
+    Sarray(SarrayData),
+    Sbit(BitData),
+    Sindir(BinaryData),
+    String,
+    Window(WindowData),
+    Index,
+}
+
+/// A numerical entry parameter, either a literal value or a reference to a CONST field or
+/// to an element of a CARRAY field
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scalar<T> {
+    Literal(T),
+    Reference {
+        field_code: CString,
+        /// element of a CARRAY, `None` for a CONST
+        index: Option<usize>,
+    },
+}
+
+impl<T> From<T> for Scalar<T> {
+    fn from(value: T) -> Self {
+        Scalar::Literal(value)
+    }
+}
+
+impl<T> Scalar<T> {
+    /// reference to the CONST field `field_code`
+    pub fn reference(field_code: &str) -> Scalar<T> {
+        Scalar::Reference {
+            field_code: CString::new(field_code).unwrap(),
+            index: None,
+        }
+    }
+    /// reference to element `index` of the CARRAY field `field_code`
+    pub fn element(field_code: &str, index: usize) -> Scalar<T> {
+        Scalar::Reference {
+            field_code: CString::new(field_code).unwrap(),
+            index: Some(index),
+        }
+    }
+}
+
+/// The type of an entry without its parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
     No,
     Bit,
-    Carry,
+    Carray,
     Const,
     Divide,
-    Lincom(LincomData),
-    Linterp(LinterpData),
+    Indir,
+    Lincom,
+    Linterp,
     Mplex,
     Multiply,
     Phase,
     Polynom,
-    Raw(RawData),
+    Raw,
     Recip,
+    Sarray,
     Sbit,
+    Sindir,
     String,
     Window,
     Index,
 }
 
+impl From<EntryKind> for ffi::gd_entype_t {
+    fn from(kind: EntryKind) -> Self {
+        match kind {
+            EntryKind::No => ffi::gd_entype_t_GD_NO_ENTRY,
+            EntryKind::Bit => ffi::gd_entype_t_GD_BIT_ENTRY,
+            EntryKind::Carray => ffi::gd_entype_t_GD_CARRAY_ENTRY,
+            EntryKind::Const => ffi::gd_entype_t_GD_CONST_ENTRY,
+            EntryKind::Divide => ffi::gd_entype_t_GD_DIVIDE_ENTRY,
+            EntryKind::Indir => ffi::gd_entype_t_GD_INDIR_ENTRY,
+            EntryKind::Lincom => ffi::gd_entype_t_GD_LINCOM_ENTRY,
+            EntryKind::Linterp => ffi::gd_entype_t_GD_LINTERP_ENTRY,
+            EntryKind::Mplex => ffi::gd_entype_t_GD_MPLEX_ENTRY,
+            EntryKind::Multiply => ffi::gd_entype_t_GD_MULTIPLY_ENTRY,
+            EntryKind::Phase => ffi::gd_entype_t_GD_PHASE_ENTRY,
+            EntryKind::Polynom => ffi::gd_entype_t_GD_POLYNOM_ENTRY,
+            EntryKind::Raw => ffi::gd_entype_t_GD_RAW_ENTRY,
+            EntryKind::Recip => ffi::gd_entype_t_GD_RECIP_ENTRY,
+            EntryKind::Sarray => ffi::gd_entype_t_GD_SARRAY_ENTRY,
+            EntryKind::Sbit => ffi::gd_entype_t_GD_SBIT_ENTRY,
+            EntryKind::Sindir => ffi::gd_entype_t_GD_SINDIR_ENTRY,
+            EntryKind::String => ffi::gd_entype_t_GD_STRING_ENTRY,
+            EntryKind::Window => ffi::gd_entype_t_GD_WINDOW_ENTRY,
+            EntryKind::Index => ffi::gd_entype_t_GD_INDEX_ENTRY,
+        }
+    }
+}
+
+impl From<ffi::gd_entype_t> for EntryKind {
+    fn from(entype: ffi::gd_entype_t) -> Self {
+        match entype {
+            ffi::gd_entype_t_GD_BIT_ENTRY => EntryKind::Bit,
+            ffi::gd_entype_t_GD_CARRAY_ENTRY => EntryKind::Carray,
+            ffi::gd_entype_t_GD_CONST_ENTRY => EntryKind::Const,
+            ffi::gd_entype_t_GD_DIVIDE_ENTRY => EntryKind::Divide,
+            ffi::gd_entype_t_GD_INDIR_ENTRY => EntryKind::Indir,
+            ffi::gd_entype_t_GD_LINCOM_ENTRY => EntryKind::Lincom,
+            ffi::gd_entype_t_GD_LINTERP_ENTRY => EntryKind::Linterp,
+            ffi::gd_entype_t_GD_MPLEX_ENTRY => EntryKind::Mplex,
+            ffi::gd_entype_t_GD_MULTIPLY_ENTRY => EntryKind::Multiply,
+            ffi::gd_entype_t_GD_PHASE_ENTRY => EntryKind::Phase,
+            ffi::gd_entype_t_GD_POLYNOM_ENTRY => EntryKind::Polynom,
+            ffi::gd_entype_t_GD_RAW_ENTRY => EntryKind::Raw,
+            ffi::gd_entype_t_GD_RECIP_ENTRY => EntryKind::Recip,
+            ffi::gd_entype_t_GD_SARRAY_ENTRY => EntryKind::Sarray,
+            ffi::gd_entype_t_GD_SBIT_ENTRY => EntryKind::Sbit,
+            ffi::gd_entype_t_GD_SINDIR_ENTRY => EntryKind::Sindir,
+            ffi::gd_entype_t_GD_STRING_ENTRY => EntryKind::String,
+            ffi::gd_entype_t_GD_WINDOW_ENTRY => EntryKind::Window,
+            ffi::gd_entype_t_GD_INDEX_ENTRY => EntryKind::Index,
+            _ => EntryKind::No,
+        }
+    }
+}
+
+impl EntryType {
+    pub fn kind(&self) -> EntryKind {
+        match self {
+            EntryType::No => EntryKind::No,
+            EntryType::Bit(_) => EntryKind::Bit,
+            EntryType::Carray(_) => EntryKind::Carray,
+            EntryType::Const(_) => EntryKind::Const,
+            EntryType::Divide(_) => EntryKind::Divide,
+            EntryType::Indir(_) => EntryKind::Indir,
+            EntryType::Lincom(_) => EntryKind::Lincom,
+            EntryType::Linterp(_) => EntryKind::Linterp,
+            EntryType::Mplex(_) => EntryKind::Mplex,
+            EntryType::Multiply(_) => EntryKind::Multiply,
+            EntryType::Phase(_) => EntryKind::Phase,
+            EntryType::Polynom(_) => EntryKind::Polynom,
+            EntryType::Raw(_) => EntryKind::Raw,
+            EntryType::Recip(_) => EntryKind::Recip,
+            EntryType::Sarray(_) => EntryKind::Sarray,
+            EntryType::Sbit(_) => EntryKind::Sbit,
+            EntryType::Sindir(_) => EntryKind::Sindir,
+            EntryType::String => EntryKind::String,
+            EntryType::Window(_) => EntryKind::Window,
+            EntryType::Index => EntryKind::Index,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct RawData {
-    pub spf: u32,
-    pub gd_type: ffi::gd_type_t,
+    pub spf: Scalar<u32>,
+    pub gd_type: GdTypes,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct LinterpData {
-    in_field: CString,
-    table: CString,
+    pub in_field: CString,
+    pub table: CString,
 }
 
+/// one to three input fields, each with its own slope and offset
+#[derive(Debug, Clone, PartialEq)]
 pub struct LincomData {
-    in_fields: (CString, CString, CString),
-    n_fields: i32,
-    m: (f64, f64, f64),
-    b: (f64, f64, f64),
+    pub in_fields: Vec<CString>,
+    pub m: Vec<Scalar<f64>>,
+    pub b: Vec<Scalar<f64>>,
+}
+
+/// used by BIT and SBIT
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitData {
+    pub in_field: CString,
+    pub bitnum: Scalar<i32>,
+    pub numbits: Scalar<i32>,
+}
+
+/// used by the two input field types DIVIDE, MULTIPLY, INDIR and SINDIR
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryData {
+    pub in_fields: (CString, CString),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseData {
+    pub in_field: CString,
+    pub shift: Scalar<i64>,
+}
+
+/// coefficients `a` in increasing order, the order of the polynomial is `a.len() - 1`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolynomData {
+    pub in_field: CString,
+    pub a: Vec<Scalar<f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipData {
+    pub in_field: CString,
+    pub dividend: Scalar<f64>,
+}
+
+/// `in_fields.1` is the field checked against the window operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowData {
+    pub in_fields: (CString, CString),
+    pub op: WindowOp,
+}
+
+/// Window operations along with their threshold
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowOp {
+    Eq(Scalar<i64>),
+    Ne(Scalar<i64>),
+    Ge(Scalar<f64>),
+    Gt(Scalar<f64>),
+    Le(Scalar<f64>),
+    Lt(Scalar<f64>),
+    Set(Scalar<u64>),
+    Clr(Scalar<u64>),
+}
+
+/// `in_fields.1` is the index field compared against `count_val`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MplexData {
+    pub in_fields: (CString, CString),
+    pub count_val: Scalar<i32>,
+    pub period: Scalar<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstData {
+    pub gd_type: GdTypes,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarrayData {
+    pub gd_type: GdTypes,
+    pub array_len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SarrayData {
+    pub array_len: usize,
+}
+
+impl BinaryData {
+    fn new(in_field1: &str, in_field2: &str) -> BinaryData {
+        BinaryData {
+            in_fields: (
+                CString::new(in_field1).unwrap(),
+                CString::new(in_field2).unwrap(),
+            ),
+        }
+    }
+}
+
+/// copies a library owned string, a null pointer becomes the empty string
+unsafe fn owned_string(ptr: *const c_char) -> CString {
+    if ptr.is_null() {
+        CString::default()
+    } else {
+        CStr::from_ptr(ptr).into()
+    }
+}
+
+/// GetData only hands out the types it knows, anything else would be a corrupt entry
+fn gd_type_from_c(gd_type: ffi::gd_type_t) -> GdTypes {
+    GdTypes::try_from(gd_type).unwrap_or(GdTypes::Null)
+}
+
+fn as_c(string: &CString) -> *mut c_char {
+    string.as_ptr() as *mut c_char
+}
+
+/// the `scalar` and `scalar_ind` arrays of a `gd_entry_t` being built
+struct ScalarSlots<'a> {
+    scalar: &'a mut [*mut c_char],
+    scalar_ind: &'a mut [c_int],
+}
+
+impl ScalarSlots<'_> {
+    /// stores a reference in `slot`, returns the value for the literal parameter
+    fn set<T: Copy + Default>(&mut self, slot: usize, param: &Scalar<T>) -> T {
+        match param {
+            Scalar::Literal(value) => *value,
+            Scalar::Reference { field_code, index } => {
+                self.scalar[slot] = as_c(field_code);
+                self.scalar_ind[slot] = index.map_or(-1, |index| index as c_int);
+                T::default()
+            }
+        }
+    }
+}
+
+/// the parameter in `slot` of a library filled entry, `value` is its literal value
+unsafe fn scalar_from_c<T>(entry_c: &ffi::gd_entry_t, slot: usize, value: T) -> Scalar<T> {
+    let field_code = entry_c.scalar[slot];
+    if field_code.is_null() {
+        return Scalar::Literal(value);
+    }
+    let index = entry_c.scalar_ind[slot];
+    Scalar::Reference {
+        field_code: owned_string(field_code),
+        index: if index < 0 {
+            None
+        } else {
+            Some(index as usize)
+        },
+    }
 }
 
 impl Entry {
     pub fn get_field_code(&self) -> &str {
         self.field.to_str().unwrap()
     }
-    fn _new(field_code: &str) -> Entry {
-        let field = CString::new(field_code).unwrap();
-        let entry_c: ffi::gd_entry_t;
+    /// the field code of the parent of a metafield, eg. `temp` for `temp/units`
+    pub fn parent(&self) -> Option<&str> {
+        self.get_field_code()
+            .rsplit_once('/')
+            .map(|(parent, _)| parent)
+    }
+    /// the name of a metafield without its parent, the whole field code otherwise
+    pub fn name(&self) -> &str {
+        let field_code = self.get_field_code();
+        field_code
+            .rsplit_once('/')
+            .map_or(field_code, |(_, name)| name)
+    }
+    /// whether the entry is hidden from listings (`/HIDDEN`)
+    pub fn hidden(&self) -> bool {
+        self.hidden
+    }
+    /// `Dirfile::add` creates the entry hidden
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+    /// index of the format file fragment the entry is defined in, 0 for the primary one
+    pub fn fragment_index(&self) -> usize {
+        self.fragment_index
+    }
+    /// fragment `Dirfile::add` puts the entry in, new entries go in fragment 0
+    pub fn set_fragment_index(&mut self, fragment_index: usize) {
+        self.fragment_index = fragment_index;
+    }
+    fn _new(field_code: &str, field_type: EntryType) -> Entry {
+        Entry {
+            field: CString::new(field_code).unwrap(),
+            field_type,
+            fragment_index: 0,
+            hidden: false,
+        }
+    }
+    /// builds the `gd_entry_t` to pass to GetData
+    ///
+    /// The strings in it point into `self`, so it must be used while `self` is borrowed
+    /// and not kept around. Parameter vectors which don't fit the entry type (eg. after
+    /// editing `field_type` by hand) fail with `GdError::Argument`.
+    pub(crate) fn to_c(&self) -> Result<ffi::gd_entry_t, GdError> {
+        self.check_params()?;
+        let mut entry_c: ffi::gd_entry_t;
         unsafe {
             entry_c = std::mem::zeroed();
         }
-        let type_data = EntryType::No;
-        let mut entry = Entry {
-            field: field,
-            field_type: type_data,
-            entry_c: entry_c,
+        entry_c.field = as_c(&self.field);
+        entry_c.fragment_index = self.fragment_index as c_int;
+        if self.hidden {
+            entry_c.flags = ffi::GD_EN_HIDDEN as c_uint;
+        }
+        let params = &mut entry_c.__bindgen_anon_1;
+        let mut slots = ScalarSlots {
+            scalar: &mut entry_c.scalar,
+            scalar_ind: &mut entry_c.scalar_ind,
         };
-        entry.entry_c.field = entry.field.as_ptr() as *mut i8;
-        entry
+        entry_c.field_type = match &self.field_type {
+            EntryType::No => ffi::gd_entype_t_GD_NO_ENTRY,
+            EntryType::Raw(raw_data) => {
+                params.__bindgen_anon_1.spf = slots.set(0, &raw_data.spf);
+                params.__bindgen_anon_1.data_type = raw_data.gd_type.into();
+                ffi::gd_entype_t_GD_RAW_ENTRY
+            }
+            EntryType::Lincom(lincom_data) => {
+                let mut m = [0.0; 3];
+                let mut b = [0.0; 3];
+                for (i, in_field) in lincom_data.in_fields.iter().enumerate() {
+                    entry_c.in_fields[i] = as_c(in_field);
+                    m[i] = slots.set(i, &lincom_data.m[i]);
+                    b[i] = slots.set(i + 3, &lincom_data.b[i]);
+                }
+                params.__bindgen_anon_2.n_fields = lincom_data.in_fields.len() as i32;
+                params.__bindgen_anon_2.m = m;
+                params.__bindgen_anon_2.b = b;
+                ffi::gd_entype_t_GD_LINCOM_ENTRY
+            }
+            EntryType::Linterp(linterp_data) => {
+                entry_c.in_fields[0] = as_c(&linterp_data.in_field);
+                params.__bindgen_anon_6.table = as_c(&linterp_data.table);
+                ffi::gd_entype_t_GD_LINTERP_ENTRY
+            }
+            EntryType::Bit(bit_data) | EntryType::Sbit(bit_data) => {
+                entry_c.in_fields[0] = as_c(&bit_data.in_field);
+                params.__bindgen_anon_4.bitnum = slots.set(0, &bit_data.bitnum);
+                params.__bindgen_anon_4.numbits = slots.set(1, &bit_data.numbits);
+                match self.field_type {
+                    EntryType::Bit(_) => ffi::gd_entype_t_GD_BIT_ENTRY,
+                    _ => ffi::gd_entype_t_GD_SBIT_ENTRY,
+                }
+            }
+            EntryType::Multiply(binary_data)
+            | EntryType::Divide(binary_data)
+            | EntryType::Indir(binary_data)
+            | EntryType::Sindir(binary_data) => {
+                entry_c.in_fields[0] = as_c(&binary_data.in_fields.0);
+                entry_c.in_fields[1] = as_c(&binary_data.in_fields.1);
+                match self.field_type {
+                    EntryType::Multiply(_) => ffi::gd_entype_t_GD_MULTIPLY_ENTRY,
+                    EntryType::Divide(_) => ffi::gd_entype_t_GD_DIVIDE_ENTRY,
+                    EntryType::Indir(_) => ffi::gd_entype_t_GD_INDIR_ENTRY,
+                    _ => ffi::gd_entype_t_GD_SINDIR_ENTRY,
+                }
+            }
+            EntryType::Phase(phase_data) => {
+                entry_c.in_fields[0] = as_c(&phase_data.in_field);
+                params.__bindgen_anon_7.shift = slots.set(0, &phase_data.shift);
+                ffi::gd_entype_t_GD_PHASE_ENTRY
+            }
+            EntryType::Polynom(polynom_data) => {
+                let mut a = [0.0; 6];
+                for (i, a_i) in polynom_data.a.iter().enumerate() {
+                    a[i] = slots.set(i, a_i);
+                }
+                entry_c.in_fields[0] = as_c(&polynom_data.in_field);
+                params.__bindgen_anon_3.poly_ord = polynom_data.a.len() as i32 - 1;
+                params.__bindgen_anon_3.a = a;
+                ffi::gd_entype_t_GD_POLYNOM_ENTRY
+            }
+            EntryType::Recip(recip_data) => {
+                entry_c.in_fields[0] = as_c(&recip_data.in_field);
+                params.__bindgen_anon_5.dividend = slots.set(0, &recip_data.dividend);
+                ffi::gd_entype_t_GD_RECIP_ENTRY
+            }
+            EntryType::Window(window_data) => {
+                entry_c.in_fields[0] = as_c(&window_data.in_fields.0);
+                entry_c.in_fields[1] = as_c(&window_data.in_fields.1);
+                let (windop, threshold) = match &window_data.op {
+                    WindowOp::Eq(i) => (
+                        ffi::gd_windop_t_GD_WINDOP_EQ,
+                        ffi::gd_triplet_t { i: slots.set(0, i) },
+                    ),
+                    WindowOp::Ne(i) => (
+                        ffi::gd_windop_t_GD_WINDOP_NE,
+                        ffi::gd_triplet_t { i: slots.set(0, i) },
+                    ),
+                    WindowOp::Ge(r) => (
+                        ffi::gd_windop_t_GD_WINDOP_GE,
+                        ffi::gd_triplet_t { r: slots.set(0, r) },
+                    ),
+                    WindowOp::Gt(r) => (
+                        ffi::gd_windop_t_GD_WINDOP_GT,
+                        ffi::gd_triplet_t { r: slots.set(0, r) },
+                    ),
+                    WindowOp::Le(r) => (
+                        ffi::gd_windop_t_GD_WINDOP_LE,
+                        ffi::gd_triplet_t { r: slots.set(0, r) },
+                    ),
+                    WindowOp::Lt(r) => (
+                        ffi::gd_windop_t_GD_WINDOP_LT,
+                        ffi::gd_triplet_t { r: slots.set(0, r) },
+                    ),
+                    WindowOp::Set(u) => (
+                        ffi::gd_windop_t_GD_WINDOP_SET,
+                        ffi::gd_triplet_t { u: slots.set(0, u) },
+                    ),
+                    WindowOp::Clr(u) => (
+                        ffi::gd_windop_t_GD_WINDOP_CLR,
+                        ffi::gd_triplet_t { u: slots.set(0, u) },
+                    ),
+                };
+                params.__bindgen_anon_8.windop = windop;
+                params.__bindgen_anon_8.threshold = threshold;
+                ffi::gd_entype_t_GD_WINDOW_ENTRY
+            }
+            EntryType::Mplex(mplex_data) => {
+                entry_c.in_fields[0] = as_c(&mplex_data.in_fields.0);
+                entry_c.in_fields[1] = as_c(&mplex_data.in_fields.1);
+                params.__bindgen_anon_9.count_val = slots.set(0, &mplex_data.count_val);
+                params.__bindgen_anon_9.period = slots.set(1, &mplex_data.period);
+                ffi::gd_entype_t_GD_MPLEX_ENTRY
+            }
+            EntryType::Const(const_data) => {
+                params.__bindgen_anon_10.const_type = const_data.gd_type.into();
+                ffi::gd_entype_t_GD_CONST_ENTRY
+            }
+            EntryType::Carray(carray_data) => {
+                params.__bindgen_anon_10.const_type = carray_data.gd_type.into();
+                params.__bindgen_anon_10.array_len = carray_data.array_len;
+                ffi::gd_entype_t_GD_CARRAY_ENTRY
+            }
+            EntryType::Sarray(sarray_data) => {
+                params.__bindgen_anon_10.array_len = sarray_data.array_len;
+                ffi::gd_entype_t_GD_SARRAY_ENTRY
+            }
+            EntryType::String => ffi::gd_entype_t_GD_STRING_ENTRY,
+            EntryType::Index => ffi::gd_entype_t_GD_INDEX_ENTRY,
+        };
+        Ok(entry_c)
+    }
+    /// the checks `new_lincom` and `new_polynom` make, for entries changed since
+    fn check_params(&self) -> Result<(), GdError> {
+        match &self.field_type {
+            EntryType::Lincom(lincom_data)
+                if !(1..=3).contains(&lincom_data.in_fields.len())
+                    || lincom_data.m.len() != lincom_data.in_fields.len()
+                    || lincom_data.b.len() != lincom_data.in_fields.len() =>
+            {
+                Err(GdError::Argument(format!(
+                    "LINCOM needs 1 to 3 input fields with one m and b each: {}",
+                    self.get_field_code()
+                )))
+            }
+            EntryType::Polynom(polynom_data) if !(2..=6).contains(&polynom_data.a.len()) => {
+                Err(GdError::Argument(format!(
+                    "POLYNOM needs 2 to 6 coefficients: {}",
+                    self.get_field_code()
+                )))
+            }
+            _ => Ok(()),
+        }
     }
-    pub fn new_raw(field_code: &str, spf: u32, gd_type: crate::GdTypes) -> Entry {
+    pub fn new_raw(
+        field_code: &str,
+        spf: impl Into<Scalar<u32>>,
+        gd_type: GdTypes,
+    ) -> Entry {
         let type_data = EntryType::Raw(RawData {
-            spf: spf,
-            gd_type: gd_type.into(),
+            spf: spf.into(),
+            gd_type,
         });
-        let mut entry = Entry::_new(field_code); //assigns "field"
-        entry.field_type = type_data; //asigns rust field type
-        entry.entry_c.field_type = ffi::gd_entype_t_GD_RAW_ENTRY;
-        entry.entry_c.__bindgen_anon_1.__bindgen_anon_1.data_type = gd_type.into();
-        entry.entry_c.__bindgen_anon_1.__bindgen_anon_1.spf = spf;
-        entry
+        Entry::_new(field_code, type_data)
     }
     pub fn new_linterp(field_code: &str, in_field: &str, table: &str) -> Entry {
-        let mut entry = Entry::_new(field_code); //assigns "field"
         let linterp_data = LinterpData {
             in_field: CString::new(in_field).unwrap(),
             table: CString::new(table).unwrap(),
         };
-
-        entry.entry_c.field_type = ffi::gd_entype_t_GD_LINTERP_ENTRY;
-        entry.entry_c.in_fields[0] = linterp_data.in_field.as_ptr() as *mut i8;
-        entry.entry_c.__bindgen_anon_1.__bindgen_anon_6.table =
-            linterp_data.table.as_ptr() as *mut i8;
-
-        let type_data = EntryType::Linterp(linterp_data);
-        entry.field_type = type_data;
-
-        entry
+        Entry::_new(field_code, EntryType::Linterp(linterp_data))
     }
-    pub fn new_lincom(
+    pub fn new_lincom<M: Into<Scalar<f64>>, B: Into<Scalar<f64>>>(
         field_code: &str,
         in_fields: Vec<&str>,
-        m: Vec<f64>,
-        b: Vec<f64>,
+        m: Vec<M>,
+        b: Vec<B>,
     ) -> Entry {
-        let mut entry = Entry::_new(field_code); //assigns "field"
-        let n_fields = in_fields.len() as i32;
-        let mut lincom_data = LincomData {
+        let n_fields = in_fields.len();
+        if !(1..=3).contains(&n_fields) || m.len() != n_fields || b.len() != n_fields {
+            panic!("Invalid number of fields");
+        }
+        let lincom_data = LincomData {
+            in_fields: in_fields
+                .into_iter()
+                .map(|in_field| CString::new(in_field).unwrap())
+                .collect(),
+            m: m.into_iter().map(Into::into).collect(),
+            b: b.into_iter().map(Into::into).collect(),
+        };
+        Entry::_new(field_code, EntryType::Lincom(lincom_data))
+    }
+    pub fn new_bit(
+        field_code: &str,
+        in_field: &str,
+        bitnum: impl Into<Scalar<i32>>,
+        numbits: impl Into<Scalar<i32>>,
+    ) -> Entry {
+        let bit_data = BitData {
+            in_field: CString::new(in_field).unwrap(),
+            bitnum: bitnum.into(),
+            numbits: numbits.into(),
+        };
+        Entry::_new(field_code, EntryType::Bit(bit_data))
+    }
+    pub fn new_sbit(
+        field_code: &str,
+        in_field: &str,
+        bitnum: impl Into<Scalar<i32>>,
+        numbits: impl Into<Scalar<i32>>,
+    ) -> Entry {
+        let bit_data = BitData {
+            in_field: CString::new(in_field).unwrap(),
+            bitnum: bitnum.into(),
+            numbits: numbits.into(),
+        };
+        Entry::_new(field_code, EntryType::Sbit(bit_data))
+    }
+    pub fn new_multiply(field_code: &str, in_field1: &str, in_field2: &str) -> Entry {
+        let binary_data = BinaryData::new(in_field1, in_field2);
+        Entry::_new(field_code, EntryType::Multiply(binary_data))
+    }
+    /// `in_field1` divided by `in_field2`
+    pub fn new_divide(field_code: &str, in_field1: &str, in_field2: &str) -> Entry {
+        let binary_data = BinaryData::new(in_field1, in_field2);
+        Entry::_new(field_code, EntryType::Divide(binary_data))
+    }
+    /// elements of the CARRAY `carray_field` indexed by the vector `index_field`
+    pub fn new_indir(field_code: &str, index_field: &str, carray_field: &str) -> Entry {
+        let binary_data = BinaryData::new(index_field, carray_field);
+        Entry::_new(field_code, EntryType::Indir(binary_data))
+    }
+    /// elements of the SARRAY `sarray_field` indexed by the vector `index_field`
+    pub fn new_sindir(field_code: &str, index_field: &str, sarray_field: &str) -> Entry {
+        let binary_data = BinaryData::new(index_field, sarray_field);
+        Entry::_new(field_code, EntryType::Sindir(binary_data))
+    }
+    pub fn new_phase(field_code: &str, in_field: &str, shift: impl Into<Scalar<i64>>) -> Entry {
+        let phase_data = PhaseData {
+            in_field: CString::new(in_field).unwrap(),
+            shift: shift.into(),
+        };
+        Entry::_new(field_code, EntryType::Phase(phase_data))
+    }
+    /// `a` holds the coefficients in increasing order, between 2 and 6 of them
+    pub fn new_polynom<A: Into<Scalar<f64>>>(field_code: &str, in_field: &str, a: Vec<A>) -> Entry {
+        if !(2..=6).contains(&a.len()) {
+            panic!("Invalid number of coefficients");
+        }
+        let polynom_data = PolynomData {
+            in_field: CString::new(in_field).unwrap(),
+            a: a.into_iter().map(Into::into).collect(),
+        };
+        Entry::_new(field_code, EntryType::Polynom(polynom_data))
+    }
+    pub fn new_recip(field_code: &str, in_field: &str, dividend: impl Into<Scalar<f64>>) -> Entry {
+        let recip_data = RecipData {
+            in_field: CString::new(in_field).unwrap(),
+            dividend: dividend.into(),
+        };
+        Entry::_new(field_code, EntryType::Recip(recip_data))
+    }
+    /// `in_field` where `check_field` satisfies `op`, zero elsewhere
+    pub fn new_window(field_code: &str, in_field: &str, check_field: &str, op: WindowOp) -> Entry {
+        let window_data = WindowData {
             in_fields: (
-                CString::new("").unwrap(),
-                CString::new("").unwrap(),
-                CString::new("").unwrap(),
+                CString::new(in_field).unwrap(),
+                CString::new(check_field).unwrap(),
             ),
-            n_fields: n_fields,
-            m: (0.0, 0.0, 0.0),
-            b: (0.0, 0.0, 0.0),
+            op,
         };
-        match n_fields {
-            1 => {
-                lincom_data.in_fields.0 = CString::new(in_fields[0]).unwrap();
-                entry.entry_c.in_fields[0] = lincom_data.in_fields.0.as_ptr() as *mut i8;
-                lincom_data.n_fields = 1;
-                lincom_data.m.0 = m[0];
-                lincom_data.b.0 = b[0];
-            }
-            2 => {
-                lincom_data.in_fields.0 = CString::new(in_fields[0]).unwrap();
-                lincom_data.in_fields.1 = CString::new(in_fields[1]).unwrap();
-                entry.entry_c.in_fields[0] = lincom_data.in_fields.0.as_ptr() as *mut i8;
-                entry.entry_c.in_fields[1] = lincom_data.in_fields.1.as_ptr() as *mut i8;
-                lincom_data.n_fields = 2;
-                lincom_data.m.0 = m[0];
-                lincom_data.m.1 = m[1];
-                lincom_data.b.0 = b[0];
-                lincom_data.b.1 = b[1];
-            }
-            3 => {
-                lincom_data.in_fields.0 = CString::new(in_fields[0]).unwrap();
-                lincom_data.in_fields.1 = CString::new(in_fields[1]).unwrap();
-                lincom_data.in_fields.2 = CString::new(in_fields[2]).unwrap();
-                entry.entry_c.in_fields[0] = lincom_data.in_fields.0.as_ptr() as *mut i8;
-                entry.entry_c.in_fields[1] = lincom_data.in_fields.1.as_ptr() as *mut i8;
-                entry.entry_c.in_fields[2] = lincom_data.in_fields.2.as_ptr() as *mut i8;
-                lincom_data.n_fields = 3;
-                lincom_data.m.0 = m[0];
-                lincom_data.m.1 = m[1];
-                lincom_data.m.2 = m[2];
-                lincom_data.b.0 = b[0];
-                lincom_data.b.1 = b[1];
-                lincom_data.b.2 = b[2];
-            }
-            _ => panic!("Invalid number of fields"),
-        }
-
-        entry.entry_c.field_type = ffi::gd_entype_t_GD_LINCOM_ENTRY;
-        entry.entry_c.__bindgen_anon_1.__bindgen_anon_2.n_fields = n_fields;
-        unsafe {
-            entry.entry_c.__bindgen_anon_1.__bindgen_anon_2.m[0] = lincom_data.m.0;
-            entry.entry_c.__bindgen_anon_1.__bindgen_anon_2.m[1] = lincom_data.m.1;
-            entry.entry_c.__bindgen_anon_1.__bindgen_anon_2.m[2] = lincom_data.m.2;
-            entry.entry_c.__bindgen_anon_1.__bindgen_anon_2.b[0] = lincom_data.b.0;
-            entry.entry_c.__bindgen_anon_1.__bindgen_anon_2.b[1] = lincom_data.b.1;
-            entry.entry_c.__bindgen_anon_1.__bindgen_anon_2.b[2] = lincom_data.b.2;
-        }
-
-        let type_data = EntryType::Lincom(lincom_data);
-        entry.field_type = type_data;
-
-        entry
+        Entry::_new(field_code, EntryType::Window(window_data))
     }
-    pub fn from_c(field_code: &str, entry_c: ffi::gd_entry_t) -> Entry {
-        let field_code_c: CString = CString::new(field_code).unwrap();
-
-        let entry_type: EntryType = unsafe {
-            match entry_c.field_type {
-                ffi::gd_entype_t_GD_RAW_ENTRY => EntryType::Raw(RawData {
-                    spf: entry_c.__bindgen_anon_1.__bindgen_anon_1.spf,
-                    gd_type: entry_c.__bindgen_anon_1.__bindgen_anon_1.data_type,
-                }),
-                ffi::gd_entype_t_GD_LINTERP_ENTRY => EntryType::Linterp(LinterpData {
-                    in_field: CString::from_raw(entry_c.in_fields[0] as *mut i8),
-                    table: CString::from_raw(
-                        entry_c.__bindgen_anon_1.__bindgen_anon_6.table as *mut i8,
-                    ),
-                }),
-                _ => panic!("Unknown entry type, memory leak!"),
+    /// `in_field` where `count_field` equals `count_val`, the last such value elsewhere
+    pub fn new_mplex(
+        field_code: &str,
+        in_field: &str,
+        count_field: &str,
+        count_val: impl Into<Scalar<i32>>,
+        period: impl Into<Scalar<i32>>,
+    ) -> Entry {
+        let mplex_data = MplexData {
+            in_fields: (
+                CString::new(in_field).unwrap(),
+                CString::new(count_field).unwrap(),
+            ),
+            count_val: count_val.into(),
+            period: period.into(),
+        };
+        Entry::_new(field_code, EntryType::Mplex(mplex_data))
+    }
+    pub fn new_const(field_code: &str, gd_type: GdTypes) -> Entry {
+        let const_data = ConstData { gd_type };
+        Entry::_new(field_code, EntryType::Const(const_data))
+    }
+    pub fn new_carray(field_code: &str, gd_type: GdTypes, array_len: usize) -> Entry {
+        let carray_data = CarrayData { gd_type, array_len };
+        Entry::_new(field_code, EntryType::Carray(carray_data))
+    }
+    pub fn new_string(field_code: &str) -> Entry {
+        Entry::_new(field_code, EntryType::String)
+    }
+    pub fn new_sarray(field_code: &str, array_len: usize) -> Entry {
+        Entry::_new(field_code, EntryType::Sarray(SarrayData { array_len }))
+    }
+    /// Takes an entry filled in by GetData (eg. by `gd_entry`), copies it into rust owned
+    /// data and releases the strings the library allocated for it
+    ///
+    /// # Safety
+    /// `entry_c` must have been filled in by GetData and its strings must not have been
+    /// freed already, they are invalid once this returns.
+    pub unsafe fn from_c(mut entry_c: ffi::gd_entry_t) -> Entry {
+        let params = &entry_c.__bindgen_anon_1;
+        let in_field = |i: usize| owned_string(entry_c.in_fields[i]);
+        let in_fields = || (in_field(0), in_field(1));
+        let entry_type: EntryType = match entry_c.field_type {
+            ffi::gd_entype_t_GD_RAW_ENTRY => EntryType::Raw(RawData {
+                spf: scalar_from_c(&entry_c, 0, params.__bindgen_anon_1.spf),
+                gd_type: gd_type_from_c(params.__bindgen_anon_1.data_type),
+            }),
+            ffi::gd_entype_t_GD_LINCOM_ENTRY => {
+                let n_fields = params.__bindgen_anon_2.n_fields as usize;
+                EntryType::Lincom(LincomData {
+                    in_fields: (0..n_fields).map(in_field).collect(),
+                    m: (0..n_fields)
+                        .map(|i| scalar_from_c(&entry_c, i, params.__bindgen_anon_2.m[i]))
+                        .collect(),
+                    b: (0..n_fields)
+                        .map(|i| scalar_from_c(&entry_c, i + 3, params.__bindgen_anon_2.b[i]))
+                        .collect(),
+                })
+            }
+            ffi::gd_entype_t_GD_LINTERP_ENTRY => EntryType::Linterp(LinterpData {
+                in_field: in_field(0),
+                table: owned_string(params.__bindgen_anon_6.table),
+            }),
+            ffi::gd_entype_t_GD_BIT_ENTRY | ffi::gd_entype_t_GD_SBIT_ENTRY => {
+                let bit_data = BitData {
+                    in_field: in_field(0),
+                    bitnum: scalar_from_c(&entry_c, 0, params.__bindgen_anon_4.bitnum),
+                    numbits: scalar_from_c(&entry_c, 1, params.__bindgen_anon_4.numbits),
+                };
+                if entry_c.field_type == ffi::gd_entype_t_GD_BIT_ENTRY {
+                    EntryType::Bit(bit_data)
+                } else {
+                    EntryType::Sbit(bit_data)
+                }
+            }
+            ffi::gd_entype_t_GD_MULTIPLY_ENTRY => EntryType::Multiply(BinaryData {
+                in_fields: in_fields(),
+            }),
+            ffi::gd_entype_t_GD_DIVIDE_ENTRY => EntryType::Divide(BinaryData {
+                in_fields: in_fields(),
+            }),
+            ffi::gd_entype_t_GD_INDIR_ENTRY => EntryType::Indir(BinaryData {
+                in_fields: in_fields(),
+            }),
+            ffi::gd_entype_t_GD_SINDIR_ENTRY => EntryType::Sindir(BinaryData {
+                in_fields: in_fields(),
+            }),
+            ffi::gd_entype_t_GD_PHASE_ENTRY => EntryType::Phase(PhaseData {
+                in_field: in_field(0),
+                shift: scalar_from_c(&entry_c, 0, params.__bindgen_anon_7.shift),
+            }),
+            ffi::gd_entype_t_GD_POLYNOM_ENTRY => {
+                let poly_ord = params.__bindgen_anon_3.poly_ord as usize;
+                EntryType::Polynom(PolynomData {
+                    in_field: in_field(0),
+                    a: (0..=poly_ord)
+                        .map(|i| scalar_from_c(&entry_c, i, params.__bindgen_anon_3.a[i]))
+                        .collect(),
+                })
+            }
+            ffi::gd_entype_t_GD_RECIP_ENTRY => EntryType::Recip(RecipData {
+                in_field: in_field(0),
+                dividend: scalar_from_c(&entry_c, 0, params.__bindgen_anon_5.dividend),
+            }),
+            ffi::gd_entype_t_GD_WINDOW_ENTRY => {
+                let threshold = params.__bindgen_anon_8.threshold;
+                let op = match params.__bindgen_anon_8.windop {
+                    ffi::gd_windop_t_GD_WINDOP_EQ => {
+                        Some(WindowOp::Eq(scalar_from_c(&entry_c, 0, threshold.i)))
+                    }
+                    ffi::gd_windop_t_GD_WINDOP_NE => {
+                        Some(WindowOp::Ne(scalar_from_c(&entry_c, 0, threshold.i)))
+                    }
+                    ffi::gd_windop_t_GD_WINDOP_GE => {
+                        Some(WindowOp::Ge(scalar_from_c(&entry_c, 0, threshold.r)))
+                    }
+                    ffi::gd_windop_t_GD_WINDOP_GT => {
+                        Some(WindowOp::Gt(scalar_from_c(&entry_c, 0, threshold.r)))
+                    }
+                    ffi::gd_windop_t_GD_WINDOP_LE => {
+                        Some(WindowOp::Le(scalar_from_c(&entry_c, 0, threshold.r)))
+                    }
+                    ffi::gd_windop_t_GD_WINDOP_LT => {
+                        Some(WindowOp::Lt(scalar_from_c(&entry_c, 0, threshold.r)))
+                    }
+                    ffi::gd_windop_t_GD_WINDOP_SET => {
+                        Some(WindowOp::Set(scalar_from_c(&entry_c, 0, threshold.u)))
+                    }
+                    ffi::gd_windop_t_GD_WINDOP_CLR => {
+                        Some(WindowOp::Clr(scalar_from_c(&entry_c, 0, threshold.u)))
+                    }
+                    _ => None,
+                };
+                match op {
+                    Some(op) => EntryType::Window(WindowData {
+                        in_fields: in_fields(),
+                        op,
+                    }),
+                    None => EntryType::No,
+                }
             }
+            ffi::gd_entype_t_GD_MPLEX_ENTRY => EntryType::Mplex(MplexData {
+                in_fields: in_fields(),
+                count_val: scalar_from_c(&entry_c, 0, params.__bindgen_anon_9.count_val),
+                period: scalar_from_c(&entry_c, 1, params.__bindgen_anon_9.period),
+            }),
+            ffi::gd_entype_t_GD_CONST_ENTRY => EntryType::Const(ConstData {
+                gd_type: gd_type_from_c(params.__bindgen_anon_10.const_type),
+            }),
+            ffi::gd_entype_t_GD_CARRAY_ENTRY => EntryType::Carray(CarrayData {
+                gd_type: gd_type_from_c(params.__bindgen_anon_10.const_type),
+                array_len: params.__bindgen_anon_10.array_len,
+            }),
+            ffi::gd_entype_t_GD_SARRAY_ENTRY => EntryType::Sarray(SarrayData {
+                array_len: params.__bindgen_anon_10.array_len,
+            }),
+            ffi::gd_entype_t_GD_STRING_ENTRY => EntryType::String,
+            ffi::gd_entype_t_GD_INDEX_ENTRY => EntryType::Index,
+            _ => EntryType::No,
         };
-        Entry {
-            field: field_code_c,
+        let entry = Entry {
+            field: owned_string(entry_c.field),
             field_type: entry_type,
-            entry_c: entry_c,
-        }
+            fragment_index: entry_c.fragment_index as usize,
+            hidden: entry_c.flags & ffi::GD_EN_HIDDEN as c_uint != 0,
+        };
+
+        //everything is copied, give the strings back to the library
+        ffi::gd_free_entry_strings(&mut entry_c);
+        entry
     }
 }
@@ -0,0 +1,256 @@
+use crate::ffi;
+use crate::{Entry, GdError};
+use std::ffi::CString;
+use std::os::raw::{c_int, c_uint};
+
+/// Options for `Dirfile::rename` and `Dirfile::move_entry`, eg.
+/// `RenameOptions::new().update_references(true)`.
+///
+/// By default only the entry itself changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenameOptions {
+    data: bool,
+    update_references: bool,
+    dangle: bool,
+    force: bool,
+}
+
+impl RenameOptions {
+    pub fn new() -> RenameOptions {
+        RenameOptions::default()
+    }
+    /// rename (or move) the data file of a RAW field to match (`GD_REN_DATA`)
+    pub fn rename_data(mut self, data: bool) -> RenameOptions {
+        self.data = data;
+        self
+    }
+    /// change the field code in the input fields and scalar parameters of the entries
+    /// which use it (`GD_REN_UPDB`)
+    pub fn update_references(mut self, update_references: bool) -> RenameOptions {
+        self.update_references = update_references;
+        self
+    }
+    /// leave aliases pointing at the old field code instead of updating them
+    /// (`GD_REN_DANGLE`)
+    pub fn dangle(mut self, dangle: bool) -> RenameOptions {
+        self.dangle = dangle;
+        self
+    }
+    /// carry on when some references can't be updated (`GD_REN_FORCE`)
+    pub fn force(mut self, force: bool) -> RenameOptions {
+        self.force = force;
+        self
+    }
+    fn flags_c(&self) -> c_uint {
+        let mut flags = 0;
+        if self.data {
+            flags |= ffi::GD_REN_DATA as c_uint;
+        }
+        if self.update_references {
+            flags |= ffi::GD_REN_UPDB as c_uint;
+        }
+        if self.dangle {
+            flags |= ffi::GD_REN_DANGLE as c_uint;
+        }
+        if self.force {
+            flags |= ffi::GD_REN_FORCE as c_uint;
+        }
+        flags
+    }
+}
+
+/// Options for `Dirfile::delete`, eg. `DeleteOptions::new().data(true)`.
+///
+/// By default deleting a field which is still used by other entries fails.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeleteOptions {
+    data: bool,
+    deref: bool,
+    force: bool,
+    meta: bool,
+}
+
+impl DeleteOptions {
+    pub fn new() -> DeleteOptions {
+        DeleteOptions::default()
+    }
+    /// delete the data file of a RAW field as well (`GD_DEL_DATA`)
+    pub fn data(mut self, data: bool) -> DeleteOptions {
+        self.data = data;
+        self
+    }
+    /// replace references to a CONST or CARRAY in other entries by its value
+    /// (`GD_DEL_DEREF`)
+    pub fn deref(mut self, deref: bool) -> DeleteOptions {
+        self.deref = deref;
+        self
+    }
+    /// delete even if other entries still use the field, leaving them dangling
+    /// (`GD_DEL_FORCE`)
+    pub fn force(mut self, force: bool) -> DeleteOptions {
+        self.force = force;
+        self
+    }
+    /// delete the metafields of the field along with it (`GD_DEL_META`)
+    pub fn meta(mut self, meta: bool) -> DeleteOptions {
+        self.meta = meta;
+        self
+    }
+    fn flags_c(&self) -> c_uint {
+        let mut flags = 0;
+        if self.data {
+            flags |= ffi::GD_DEL_DATA as c_uint;
+        }
+        if self.deref {
+            flags |= ffi::GD_DEL_DEREF as c_uint;
+        }
+        if self.force {
+            flags |= ffi::GD_DEL_FORCE as c_uint;
+        }
+        if self.meta {
+            flags |= ffi::GD_DEL_META as c_uint;
+        }
+        flags
+    }
+}
+
+/// Changing the entries already in the dirfile
+impl crate::Dirfile {
+    /// replaces the definition of `field_code` with `entry` (the field code and fragment
+    /// of `entry` are ignored, use `rename` and `move_entry` for those)
+    ///
+    /// With `recode` the data on disk is converted so the field keeps its values, eg.
+    /// the RAW file is rewritten when its type or samples per frame change. Otherwise
+    /// only the metadata changes.
+    pub fn alter_entry(
+        &mut self,
+        field_code: &str,
+        entry: &Entry,
+        recode: bool,
+    ) -> Result<(), GdError> {
+        debug_call!(self, "alter_entry", field_code, recode);
+        let entry_c = entry.to_c()?;
+        let field_code = CString::new(field_code).unwrap();
+        let ret_val = unsafe {
+            ffi::gd_alter_entry(
+                self.dirfile.as_ptr(),
+                field_code.as_ptr(),
+                &entry_c,
+                recode as c_int,
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    /// like `alter_entry`, with the new definition given as a format file line, eg.
+    /// `"cal LINCOM raw 2 0"`
+    pub fn alter_spec(&mut self, line: &str, recode: bool) -> Result<(), GdError> {
+        debug_call!(self, "alter_spec", line, recode);
+        let line = CString::new(line).unwrap();
+        let ret_val =
+            unsafe { ffi::gd_alter_spec(self.dirfile.as_ptr(), line.as_ptr(), recode as c_int) };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    pub fn rename(
+        &mut self,
+        field_code: &str,
+        new_name: &str,
+        options: &RenameOptions,
+    ) -> Result<(), GdError> {
+        debug_call!(self, "rename", field_code, new_name);
+        let field_code = CString::new(field_code).unwrap();
+        let new_name = CString::new(new_name).unwrap();
+        let ret_val = unsafe {
+            ffi::gd_rename(
+                self.dirfile.as_ptr(),
+                field_code.as_ptr(),
+                new_name.as_ptr(),
+                options.flags_c(),
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    /// moves an entry to another fragment
+    ///
+    /// If the fragments have different affixes the field code changes accordingly, so
+    /// `options` apply as for `rename`.
+    pub fn move_entry(
+        &mut self,
+        field_code: &str,
+        fragment_index: usize,
+        options: &RenameOptions,
+    ) -> Result<(), GdError> {
+        debug_call!(self, "move_entry", field_code, fragment_index);
+        let field_code = CString::new(field_code).unwrap();
+        let ret_val = unsafe {
+            ffi::gd_move(
+                self.dirfile.as_ptr(),
+                field_code.as_ptr(),
+                fragment_index as c_int,
+                options.flags_c(),
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    pub fn delete(&mut self, field_code: &str, options: &DeleteOptions) -> Result<(), GdError> {
+        debug_call!(self, "delete", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let ret_val = unsafe {
+            ffi::gd_delete(
+                self.dirfile.as_ptr(),
+                field_code.as_ptr(),
+                options.flags_c(),
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    /// hides a field from listings (`/HIDDEN`), it can still be used as normal
+    pub fn hide(&mut self, field_code: &str) -> Result<(), GdError> {
+        debug_call!(self, "hide", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let ret_val = unsafe { ffi::gd_hide(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    pub fn unhide(&mut self, field_code: &str) -> Result<(), GdError> {
+        debug_call!(self, "unhide", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let ret_val = unsafe { ffi::gd_unhide(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    pub fn hidden(&self, field_code: &str) -> Result<bool, GdError> {
+        debug_call!(self, "hidden", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let hidden = unsafe { ffi::gd_hidden(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if hidden < 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(hidden != 0)
+    }
+}
@@ -0,0 +1,173 @@
+use crate::ffi;
+use crate::{copy_string_list, Entry, EntryKind, GdError};
+use std::ffi::CString;
+use std::os::raw::{c_int, c_uint};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryClass {
+    All,
+    Vector,
+    Scalar,
+    Alias,
+    Kind(EntryKind),
+}
+
+/// Selects the entries listed by `Dirfile::field_codes`, `Dirfile::entries` and
+/// `Dirfile::nentries`, eg. `EntryFilter::new().kind(EntryKind::Raw).include_hidden(true)`.
+///
+/// By default every entry type is listed, aliases are included and hidden entries are not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryFilter {
+    class: EntryClass,
+    hidden: bool,
+    aliases: bool,
+}
+
+impl Default for EntryFilter {
+    fn default() -> Self {
+        EntryFilter {
+            class: EntryClass::All,
+            hidden: false,
+            aliases: true,
+        }
+    }
+}
+
+impl EntryFilter {
+    pub fn new() -> EntryFilter {
+        EntryFilter::default()
+    }
+    /// only entries of type `kind`
+    pub fn kind(mut self, kind: EntryKind) -> EntryFilter {
+        self.class = EntryClass::Kind(kind);
+        self
+    }
+    /// only vector fields (RAW and all derived types)
+    pub fn vectors(mut self) -> EntryFilter {
+        self.class = EntryClass::Vector;
+        self
+    }
+    /// only scalar fields (CONST, CARRAY, STRING and SARRAY)
+    pub fn scalars(mut self) -> EntryFilter {
+        self.class = EntryClass::Scalar;
+        self
+    }
+    /// only aliases
+    pub fn aliases(mut self) -> EntryFilter {
+        self.class = EntryClass::Alias;
+        self.aliases = true;
+        self
+    }
+    pub fn include_hidden(mut self, hidden: bool) -> EntryFilter {
+        self.hidden = hidden;
+        self
+    }
+    pub fn include_aliases(mut self, aliases: bool) -> EntryFilter {
+        self.aliases = aliases;
+        self
+    }
+    fn type_c(&self) -> c_int {
+        match self.class {
+            EntryClass::All => ffi::GD_ALL_ENTRIES as c_int,
+            EntryClass::Vector => ffi::GD_VECTOR_ENTRIES as c_int,
+            EntryClass::Scalar => ffi::GD_SCALAR_ENTRIES as c_int,
+            EntryClass::Alias => ffi::gd_entype_t_GD_ALIAS_ENTRY as c_int,
+            EntryClass::Kind(kind) => ffi::gd_entype_t::from(kind) as c_int,
+        }
+    }
+    fn flags_c(&self) -> c_uint {
+        let mut flags = 0;
+        if self.hidden {
+            flags |= ffi::GD_HIDDEN_ENTRIES as c_uint;
+        }
+        if !self.aliases {
+            flags |= ffi::GD_NOALIAS_ENTRIES as c_uint;
+        }
+        flags
+    }
+}
+
+impl crate::Dirfile {
+    /// number of entries selected by `filter`
+    pub fn nentries(&self, filter: &EntryFilter) -> Result<usize, GdError> {
+        debug_call!(self, "nentries");
+        self._nentries(None, filter)
+    }
+
+    /// number of metafields of `parent` selected by `filter`
+    pub fn nmetafields(&self, parent: &str, filter: &EntryFilter) -> Result<usize, GdError> {
+        debug_call!(self, "nmetafields", parent);
+        let parent = CString::new(parent).unwrap();
+        self._nentries(Some(&parent), filter)
+    }
+
+    fn _nentries(&self, parent: Option<&CString>, filter: &EntryFilter) -> Result<usize, GdError> {
+        let parent = parent.map_or(std::ptr::null(), |parent| parent.as_ptr());
+        let n = unsafe {
+            ffi::gd_nentries(
+                self.dirfile.as_ptr(),
+                parent,
+                filter.type_c(),
+                filter.flags_c(),
+            )
+        };
+        if n == 0 {
+            if let Some(error) = self.last_error() {
+                return Err(error);
+            }
+        }
+        Ok(n as usize)
+    }
+
+    /// field codes of the entries selected by `filter`
+    pub fn field_codes(
+        &self,
+        filter: &EntryFilter,
+    ) -> Result<impl Iterator<Item = String>, GdError> {
+        debug_call!(self, "field_codes");
+        self._field_codes(None, filter)
+    }
+
+    /// names of the metafields of `parent` selected by `filter`, without the `parent/`
+    /// part of their field codes
+    pub fn metafields(
+        &self,
+        parent: &str,
+        filter: &EntryFilter,
+    ) -> Result<impl Iterator<Item = String>, GdError> {
+        debug_call!(self, "metafields", parent);
+        let parent = CString::new(parent).unwrap();
+        self._field_codes(Some(&parent), filter)
+    }
+
+    fn _field_codes(
+        &self,
+        parent: Option<&CString>,
+        filter: &EntryFilter,
+    ) -> Result<std::vec::IntoIter<String>, GdError> {
+        let parent = parent.map_or(std::ptr::null(), |parent| parent.as_ptr());
+        let list = unsafe {
+            ffi::gd_entry_list(
+                self.dirfile.as_ptr(),
+                parent,
+                filter.type_c(),
+                filter.flags_c(),
+            )
+        };
+        if list.is_null() {
+            return Err(self.last_error().unwrap());
+        }
+        let field_codes = unsafe { copy_string_list(list) };
+        Ok(field_codes.into_iter())
+    }
+
+    /// the entries selected by `filter`, each one is looked up when the iterator reaches it
+    pub fn entries(
+        &self,
+        filter: &EntryFilter,
+    ) -> Result<impl Iterator<Item = Result<Entry, GdError>> + '_, GdError> {
+        debug_call!(self, "entries", filter);
+        let field_codes = self.field_codes(filter)?;
+        Ok(field_codes.map(move |field_code| self.get_entry(&field_code)))
+    }
+}
@@ -0,0 +1,164 @@
+use crate::ffi;
+use crate::{invertible, Dirfile, GdError, GdNative};
+use std::ffi::CString;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+/// A vector field looked up once for repeated reads and writes of `T`, from
+/// `Dirfile::field`
+///
+/// The handle borrows the `Dirfile`, so the metadata can't change (eg. through `delete`
+/// or a `desync` reload) while it is alive. Any number of handles can be held at once:
+/// as on `Dirfile`, reading, writing and flushing data only need `&self`, so the data I/O
+/// of the `Dirfile` (eg. `Dirfile::flush`) can be mixed with the handles.
+pub struct Field<'a, T: GdNative> {
+    dirfile: &'a Dirfile,
+    field_code: CString,
+    spf: u32,
+    writable: bool,
+    data_type: PhantomData<T>,
+}
+
+impl Dirfile {
+    /// resolves a vector field (RAW or derived) for reading and writing `T`
+    ///
+    /// Fails if the field does not exist or is a scalar field. Whether it can be written
+    /// is checked here as well, but only reported by `Field::write`.
+    pub fn field<T: GdNative>(&self, field_code: &str) -> Result<Field<'_, T>, GdError> {
+        debug_call!(self, "field", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let field_type = unsafe { ffi::gd_entry_type(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if field_type == ffi::gd_entype_t_GD_NO_ENTRY {
+            return Err(self.last_error().unwrap());
+        }
+        let spf = unsafe { ffi::gd_spf(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if spf == 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(Field {
+            dirfile: self,
+            field_code,
+            spf,
+            writable: invertible(field_type),
+            data_type: PhantomData,
+        })
+    }
+}
+
+impl<T: GdNative> Field<'_, T> {
+    pub fn field_code(&self) -> &str {
+        self.field_code.to_str().unwrap()
+    }
+
+    pub fn spf(&self) -> u32 {
+        self.spf
+    }
+
+    /// writes `data` at the I/O pointer of the field, returns the number of samples written
+    ///
+    /// Like `Dirfile::putdata`, without looking the field up again.
+    pub fn write(&self, data: &[T]) -> Result<usize, GdError> {
+        debug_call!(
+            self.dirfile,
+            "Field::write",
+            field_code = self.field_code(),
+            num_samples = data.len()
+        );
+        if !self.writable {
+            return Err(GdError::BadFieldType(format!(
+                "Cannot write to non-invertible field: {}",
+                self.field_code()
+            )));
+        }
+        let write_n = unsafe {
+            ffi::gd_putdata(
+                self.dirfile.dirfile.as_ptr(),
+                self.field_code.as_ptr(),
+                ffi::GD_HERE.into(),
+                0,
+                0,
+                data.len(),
+                T::GD_TYPE.into(),
+                data.as_ptr() as *const std::ffi::c_void,
+            )
+        };
+        if write_n != data.len() {
+            if let Some(error) = self.dirfile.field_error(&self.field_code) {
+                return Err(error);
+            }
+        }
+        Ok(write_n)
+    }
+
+    /// reads the samples with sample numbers in `samples`
+    ///
+    /// A range running past the end of the field is not an error, the returned vector is
+    /// just shorter.
+    pub fn read(&self, samples: Range<u64>) -> Result<Vec<T>, GdError> {
+        debug_call!(
+            self.dirfile,
+            "Field::read",
+            field_code = self.field_code(),
+            samples
+        );
+        let num_samples = samples.end.saturating_sub(samples.start) as usize;
+        let mut data: Vec<T> = vec![T::default(); num_samples];
+        let read_n = unsafe {
+            ffi::gd_getdata(
+                self.dirfile.dirfile.as_ptr(),
+                self.field_code.as_ptr(),
+                0,
+                samples.start as i64,
+                0,
+                num_samples,
+                T::GD_TYPE.into(),
+                data.as_mut_ptr() as *mut std::ffi::c_void,
+            )
+        };
+        if read_n != num_samples {
+            if let Some(error) = self.dirfile.field_error(&self.field_code) {
+                return Err(error);
+            }
+            data.truncate(read_n);
+        }
+        Ok(data)
+    }
+
+    /// writes the data of the field to disk and closes its file, it is reopened by the
+    /// next read or write (`Dirfile::flush` for a single field)
+    pub fn flush(&self) -> Result<(), GdError> {
+        debug_call!(self.dirfile, "Field::flush", field_code = self.field_code());
+        let ret_val =
+            unsafe { ffi::gd_flush(self.dirfile.dirfile.as_ptr(), self.field_code.as_ptr()) };
+        if ret_val != 0 {
+            return Err(self.dirfile.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    /// writes the data of the field to disk, leaving its file open (`Dirfile::sync` for a
+    /// single field)
+    pub fn sync(&self) -> Result<(), GdError> {
+        debug_call!(self.dirfile, "Field::sync", field_code = self.field_code());
+        let ret_val =
+            unsafe { ffi::gd_sync(self.dirfile.dirfile.as_ptr(), self.field_code.as_ptr()) };
+        if ret_val != 0 {
+            return Err(self.dirfile.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    /// number of complete frames in the field
+    pub fn nframes(&self) -> Result<u64, GdError> {
+        debug_call!(
+            self.dirfile,
+            "Field::nframes",
+            field_code = self.field_code()
+        );
+        let eof = unsafe { ffi::gd_eof(self.dirfile.dirfile.as_ptr(), self.field_code.as_ptr()) };
+        if eof < 0 {
+            return Err(self.dirfile.field_error(&self.field_code).unwrap());
+        }
+        Ok(eof as u64 / self.spf as u64)
+    }
+}
@@ -0,0 +1,369 @@
+use crate::ffi;
+use crate::{take_string, Endianness, GdError, OpenOptions};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_ulong};
+use std::path::PathBuf;
+
+/// The format file fragments an operation applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fragment {
+    /// a single fragment, 0 is the primary format file
+    Index(usize),
+    /// every fragment (`GD_ALL_FRAGMENTS`)
+    All,
+}
+
+impl From<usize> for Fragment {
+    fn from(fragment_index: usize) -> Self {
+        Fragment::Index(fragment_index)
+    }
+}
+
+impl From<Fragment> for c_int {
+    fn from(fragment: Fragment) -> Self {
+        match fragment {
+            Fragment::Index(fragment_index) => fragment_index as c_int,
+            Fragment::All => ffi::GD_ALL_FRAGMENTS as c_int,
+        }
+    }
+}
+
+/// What a fragment is protected against, see `Dirfile::alter_protection`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protection {
+    None,
+    /// the metadata in the format file can't be changed
+    Format,
+    /// the RAW data can't be written
+    Data,
+    /// both the metadata and the RAW data
+    All,
+}
+
+impl From<Protection> for c_int {
+    fn from(protection: Protection) -> Self {
+        let protection = match protection {
+            Protection::None => ffi::GD_PROTECT_NONE,
+            Protection::Format => ffi::GD_PROTECT_FORMAT,
+            Protection::Data => ffi::GD_PROTECT_DATA,
+            Protection::All => ffi::GD_PROTECT_ALL,
+        };
+        protection as c_int
+    }
+}
+
+impl From<c_int> for Protection {
+    fn from(protection: c_int) -> Self {
+        let format = protection & ffi::GD_PROTECT_FORMAT as c_int != 0;
+        let data = protection & ffi::GD_PROTECT_DATA as c_int != 0;
+        match (format, data) {
+            (false, false) => Protection::None,
+            (true, false) => Protection::Format,
+            (false, true) => Protection::Data,
+            (true, true) => Protection::All,
+        }
+    }
+}
+
+/// `Some` as a C string, `None` as a null pointer
+fn optional_c(value: &Option<CString>) -> *const c_char {
+    value
+        .as_ref()
+        .map_or(std::ptr::null(), |value| value.as_ptr())
+}
+
+/// Format file fragments, the primary format file is fragment 0 and every `/INCLUDE`
+/// adds one more
+impl crate::Dirfile {
+    /// includes the fragment `file` (relative to the directory of `parent_fragment`) in
+    /// `parent_fragment`, returns the index of the new fragment
+    ///
+    /// Creation, truncation, encoding, byte order and parser flags are taken from
+    /// `options`, eg. `OpenOptions::new().create(true)` to make a new empty fragment.
+    pub fn include(
+        &mut self,
+        file: &str,
+        parent_fragment: usize,
+        options: &OpenOptions,
+    ) -> Result<usize, GdError> {
+        self.include_affix(file, parent_fragment, None, None, options)
+    }
+
+    /// like `include`, but every field code in the new fragment gets `prefix` and
+    /// `suffix` added
+    pub fn include_affix(
+        &mut self,
+        file: &str,
+        parent_fragment: usize,
+        prefix: Option<&str>,
+        suffix: Option<&str>,
+        options: &OpenOptions,
+    ) -> Result<usize, GdError> {
+        debug_call!(self, "include_affix", file, parent_fragment, prefix, suffix);
+        let file = CString::new(file).unwrap();
+        let prefix = prefix.map(|prefix| CString::new(prefix).unwrap());
+        let suffix = suffix.map(|suffix| CString::new(suffix).unwrap());
+        //access mode belongs to the dirfile, not the fragment
+        let flags = options.flags() & !(ffi::GD_RDWR as c_ulong);
+        let fragment_index = unsafe {
+            ffi::gd_include_affix(
+                self.dirfile.as_ptr(),
+                file.as_ptr(),
+                parent_fragment as c_int,
+                optional_c(&prefix),
+                optional_c(&suffix),
+                flags,
+            )
+        };
+        if fragment_index < 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(fragment_index as usize)
+    }
+
+    /// removes a fragment (and the fragments it includes) from the dirfile, when `delete`
+    /// is set the format files are deleted from disk as well
+    pub fn uninclude(&mut self, fragment_index: usize, delete: bool) -> Result<(), GdError> {
+        debug_call!(self, "uninclude", fragment_index, delete);
+        let ret_val = unsafe {
+            ffi::gd_uninclude(
+                self.dirfile.as_ptr(),
+                fragment_index as c_int,
+                delete as c_int,
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    pub fn nfragments(&self) -> Result<usize, GdError> {
+        debug_call!(self, "nfragments");
+        let n = unsafe { ffi::gd_nfragments(self.dirfile.as_ptr()) };
+        if n <= 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(n as usize)
+    }
+
+    /// path of the format file of a fragment
+    pub fn fragment_name(&self, fragment_index: usize) -> Result<PathBuf, GdError> {
+        debug_call!(self, "fragment_name", fragment_index);
+        let name = unsafe { ffi::gd_fragmentname(self.dirfile.as_ptr(), fragment_index as c_int) };
+        if name.is_null() {
+            return Err(self.last_error().unwrap());
+        }
+        //the string belongs to the library
+        let name = unsafe { CStr::from_ptr(name) }.to_string_lossy();
+        Ok(PathBuf::from(&*name))
+    }
+
+    /// index of the fragment which includes `fragment_index`, the primary format file
+    /// (fragment 0) has no parent and returns an error
+    pub fn parent_fragment(&self, fragment_index: usize) -> Result<usize, GdError> {
+        debug_call!(self, "parent_fragment", fragment_index);
+        let parent =
+            unsafe { ffi::gd_parent_fragment(self.dirfile.as_ptr(), fragment_index as c_int) };
+        if parent < 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(parent as usize)
+    }
+
+    /// index of the fragment a field is defined in
+    pub fn fragment_index(&self, field_code: &str) -> Result<usize, GdError> {
+        debug_call!(self, "fragment_index", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let fragment_index =
+            unsafe { ffi::gd_fragment_index(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if fragment_index < 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(fragment_index as usize)
+    }
+
+    /// the prefix and suffix applied to the field codes of a fragment
+    pub fn fragment_affixes(&self, fragment_index: usize) -> Result<(String, String), GdError> {
+        debug_call!(self, "fragment_affixes", fragment_index);
+        let mut prefix: *mut c_char = std::ptr::null_mut();
+        let mut suffix: *mut c_char = std::ptr::null_mut();
+        let ret_val = unsafe {
+            ffi::gd_fragment_affixes(
+                self.dirfile.as_ptr(),
+                fragment_index as c_int,
+                &mut prefix,
+                &mut suffix,
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(unsafe { (take_string(prefix), take_string(suffix)) })
+    }
+
+    /// changes the prefix and/or suffix of a fragment (`None` leaves it unchanged), the
+    /// field codes defined in it change accordingly
+    pub fn alter_affixes(
+        &mut self,
+        fragment_index: usize,
+        prefix: Option<&str>,
+        suffix: Option<&str>,
+    ) -> Result<(), GdError> {
+        debug_call!(self, "alter_affixes", fragment_index, prefix, suffix);
+        let prefix = prefix.map(|prefix| CString::new(prefix).unwrap());
+        let suffix = suffix.map(|suffix| CString::new(suffix).unwrap());
+        let ret_val = unsafe {
+            ffi::gd_alter_affixes(
+                self.dirfile.as_ptr(),
+                fragment_index as c_int,
+                optional_c(&prefix),
+                optional_c(&suffix),
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    /// writes the format file of a fragment to disk even if it has not been modified
+    pub fn rewrite_fragment(&mut self, fragment: impl Into<Fragment>) -> Result<(), GdError> {
+        let fragment: Fragment = fragment.into();
+        debug_call!(self, "rewrite_fragment", fragment);
+        let ret_val =
+            unsafe { ffi::gd_rewrite_fragment(self.dirfile.as_ptr(), c_int::from(fragment)) };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    /// byte order of the RAW data of a fragment
+    pub fn endianness(&self, fragment_index: usize) -> Result<Endianness, GdError> {
+        debug_call!(self, "endianness", fragment_index);
+        let flags = self.endianness_flags(fragment_index)?;
+        if flags & ffi::GD_BIG_ENDIAN as c_ulong != 0 {
+            Ok(Endianness::Big)
+        } else {
+            Ok(Endianness::Little)
+        }
+    }
+
+    /// whether double precision floats in a fragment are stored in ARM middle-endian order
+    pub fn arm_endian(&self, fragment_index: usize) -> Result<bool, GdError> {
+        debug_call!(self, "arm_endian", fragment_index);
+        let flags = self.endianness_flags(fragment_index)?;
+        Ok(flags & ffi::GD_ARM_ENDIAN as c_ulong != 0)
+    }
+
+    fn endianness_flags(&self, fragment_index: usize) -> Result<c_ulong, GdError> {
+        let flags = unsafe { ffi::gd_endianness(self.dirfile.as_ptr(), fragment_index as c_int) };
+        if flags == 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(flags)
+    }
+
+    /// changes the byte order of one or all fragments, with `recode` the existing RAW
+    /// files are byte swapped to match, otherwise only the metadata changes
+    pub fn alter_endianness(
+        &mut self,
+        endianness: Endianness,
+        arm_endian: bool,
+        fragment: impl Into<Fragment>,
+        recode: bool,
+    ) -> Result<(), GdError> {
+        let fragment: Fragment = fragment.into();
+        debug_call!(self, "alter_endianness", endianness, arm_endian, fragment, recode);
+        let mut byte_sex = c_ulong::from(endianness);
+        if arm_endian {
+            byte_sex |= ffi::GD_ARM_ENDIAN as c_ulong;
+        }
+        let ret_val = unsafe {
+            ffi::gd_alter_endianness(
+                self.dirfile.as_ptr(),
+                byte_sex,
+                fragment.into(),
+                recode as c_int,
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    /// the frame number of the first frame of the RAW data of a fragment
+    pub fn frame_offset(&self, fragment_index: usize) -> Result<u64, GdError> {
+        debug_call!(self, "frame_offset", fragment_index);
+        let offset = unsafe { ffi::gd_frameoffset(self.dirfile.as_ptr(), fragment_index as c_int) };
+        if offset < 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(offset as u64)
+    }
+
+    /// changes the frame offset of one or all fragments
+    ///
+    /// With `recode` the RAW files are shifted so every sample keeps its frame number
+    /// (dropping or zero padding data at the start), otherwise the data stays where it is
+    /// on disk and so moves to different frame numbers.
+    pub fn alter_frame_offset(
+        &mut self,
+        offset: u64,
+        fragment: impl Into<Fragment>,
+        recode: bool,
+    ) -> Result<(), GdError> {
+        let fragment: Fragment = fragment.into();
+        debug_call!(self, "alter_frame_offset", offset, fragment, recode);
+        let ret_val = unsafe {
+            ffi::gd_alter_frameoffset(
+                self.dirfile.as_ptr(),
+                offset as ffi::off_t,
+                fragment.into(),
+                recode as c_int,
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    /// protection level of a fragment
+    pub fn protection(&self, fragment_index: usize) -> Result<Protection, GdError> {
+        debug_call!(self, "protection", fragment_index);
+        let protection =
+            unsafe { ffi::gd_protection(self.dirfile.as_ptr(), fragment_index as c_int) };
+        if protection < 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(Protection::from(protection))
+    }
+
+    /// changes the protection level of one or all fragments
+    ///
+    /// Once protected, changing the metadata or writing the data of the fragment fails
+    /// with `GdError::Protected` until the protection is lowered again. The protection
+    /// only binds GetData based tools, it is not a file permission.
+    pub fn alter_protection(
+        &mut self,
+        protection: Protection,
+        fragment: impl Into<Fragment>,
+    ) -> Result<(), GdError> {
+        let fragment: Fragment = fragment.into();
+        debug_call!(self, "alter_protection", protection, fragment);
+        let ret_val = unsafe {
+            ffi::gd_alter_protection(
+                self.dirfile.as_ptr(),
+                protection.into(),
+                fragment.into(),
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use std::error;
 use std::fmt;
 use crate::ffi;
-use std::ffi::CString;
+use crate::{take_string, Encoding};
 
 
 
@@ -38,8 +38,7 @@ pub enum GdError {
     Range(String),           // GD_E_RANGE
     RecurseLevel(String),    // GD_E_RECURSE_LEVEL
     UncleanDb(String),       // GD_E_UNCLEAN_DB
-    UnknownEncoding(String), // GD_E_UNKNOWN_ENCODING
-    Unsupported(String),     // GD_E_UNSUPPORTED
+    UnsupportedEncoding(Encoding, String), // GD_E_UNKNOWN_ENCODING and GD_E_UNSUPPORTED, `Encoding::Unknown` if it could not be worked out
 }
 impl GdError {
     pub fn message(&self) -> &str {
@@ -72,8 +71,7 @@ impl GdError {
             GdError::Range(msg) => msg,
             GdError::RecurseLevel(msg) => msg,
             GdError::UncleanDb(msg) => msg,
-            GdError::UnknownEncoding(msg) => msg,
-            GdError::Unsupported(msg) => msg,
+            GdError::UnsupportedEncoding(_, msg) => msg,
         }
     }
 }
@@ -93,49 +91,59 @@ impl error::Error for GdError {
 
 impl crate::Dirfile{
     pub fn get_error(&self) -> Option<GdError> {
-        let error = unsafe { ffi::gd_error(self.dirfile.unwrap().as_ptr()) };
+        let error = unsafe { ffi::gd_error(self.dirfile.as_ptr()) };
         if error == ffi::GD_E_OK as i32 {
             return None;
         }
         let error_string_ptr = unsafe {
-            ffi::gd_error_string(self.dirfile.unwrap().as_ptr(), std::ptr::null_mut(), 0)
+            ffi::gd_error_string(self.dirfile.as_ptr(), std::ptr::null_mut(), 0)
         };
-        let error_string_c = unsafe { CString::from_raw(error_string_ptr as *mut i8) }; //takes ownership of the pointer
-        let error_string = error_string_c.to_str().unwrap().to_string(); //error wants to own the string
-        match error {
-            ffi::GD_E_ALLOC => Some(GdError::Alloc(error_string)),
-            ffi::GD_E_ACCMODE => Some(GdError::Accmode(error_string)),
-            ffi::GD_E_ARGUMENT => Some(GdError::Argument(error_string)),
-            ffi::GD_E_BAD_CODE => Some(GdError::BadCode(error_string)),
-            ffi::GD_E_BAD_DIRFILE => Some(GdError::BadDirfile(error_string)),
-            ffi::GD_E_BAD_ENTRY => Some(GdError::BadEntry(error_string)),
-            ffi::GD_E_BAD_FIELD_TYPE => Some(GdError::BadFieldType(error_string)),
-            ffi::GD_E_BAD_INDEX => Some(GdError::BadIndex(error_string)),
-            ffi::GD_E_BAD_REFERENCE => Some(GdError::BadReference(error_string)),
-            ffi::GD_E_BAD_SCALAR => Some(GdError::BadScalar(error_string)),
-            ffi::GD_E_BAD_TYPE => Some(GdError::BadType(error_string)),
-            ffi::GD_E_BOUNDS => Some(GdError::Bounds(error_string)),
-            ffi::GD_E_CALLBACK => Some(GdError::Callback(error_string)),
-            ffi::GD_E_CREAT => Some(GdError::Creat(error_string)),
-            ffi::GD_E_DELETE => Some(GdError::Delete(error_string)),
-            ffi::GD_E_DIMENSION => Some(GdError::Dimension(error_string)),
-            ffi::GD_E_DOMAIN => Some(GdError::Domain(error_string)),
-            ffi::GD_E_DUPLICATE => Some(GdError::Duplicate(error_string)),
-            ffi::GD_E_EXISTS => Some(GdError::Exists(error_string)),
-            ffi::GD_E_FORMAT => Some(GdError::Format(error_string)),
-            ffi::GD_E_INTERNAL_ERROR => Some(GdError::InternalError(error_string)),
-            ffi::GD_E_IO => Some(GdError::Io(error_string)),
-            ffi::GD_E_LINE_TOO_LONG => Some(GdError::LineTooLong(error_string)),
-            ffi::GD_E_LUT => Some(GdError::Lut(error_string)),
-            ffi::GD_E_PROTECTED => Some(GdError::Protected(error_string)),
-            ffi::GD_E_RANGE => Some(GdError::Range(error_string)),
-            ffi::GD_E_RECURSE_LEVEL => Some(GdError::RecurseLevel(error_string)),
-            ffi::GD_E_UNCLEAN_DB => Some(GdError::UncleanDb(error_string)),
-            ffi::GD_E_UNKNOWN_ENCODING => Some(GdError::UnknownEncoding(error_string)),
-            ffi::GD_E_UNSUPPORTED => Some(GdError::Unsupported(error_string)),
+        let error_string = unsafe { take_string(error_string_ptr) };
+        let error = match error {
+            ffi::GD_E_ALLOC => GdError::Alloc(error_string),
+            ffi::GD_E_ACCMODE => GdError::Accmode(error_string),
+            ffi::GD_E_ARGUMENT => GdError::Argument(error_string),
+            ffi::GD_E_BAD_CODE => GdError::BadCode(error_string),
+            ffi::GD_E_BAD_DIRFILE => GdError::BadDirfile(error_string),
+            ffi::GD_E_BAD_ENTRY => GdError::BadEntry(error_string),
+            ffi::GD_E_BAD_FIELD_TYPE => GdError::BadFieldType(error_string),
+            ffi::GD_E_BAD_INDEX => GdError::BadIndex(error_string),
+            ffi::GD_E_BAD_REFERENCE => GdError::BadReference(error_string),
+            ffi::GD_E_BAD_SCALAR => GdError::BadScalar(error_string),
+            ffi::GD_E_BAD_TYPE => GdError::BadType(error_string),
+            ffi::GD_E_BOUNDS => GdError::Bounds(error_string),
+            ffi::GD_E_CALLBACK => GdError::Callback(error_string),
+            ffi::GD_E_CREAT => GdError::Creat(error_string),
+            ffi::GD_E_DELETE => GdError::Delete(error_string),
+            ffi::GD_E_DIMENSION => GdError::Dimension(error_string),
+            ffi::GD_E_DOMAIN => GdError::Domain(error_string),
+            ffi::GD_E_DUPLICATE => GdError::Duplicate(error_string),
+            ffi::GD_E_EXISTS => GdError::Exists(error_string),
+            ffi::GD_E_FORMAT => GdError::Format(error_string),
+            ffi::GD_E_INTERNAL_ERROR => GdError::InternalError(error_string),
+            ffi::GD_E_IO => GdError::Io(error_string),
+            ffi::GD_E_LINE_TOO_LONG => GdError::LineTooLong(error_string),
+            ffi::GD_E_LUT => GdError::Lut(error_string),
+            ffi::GD_E_PROTECTED => GdError::Protected(error_string),
+            ffi::GD_E_RANGE => GdError::Range(error_string),
+            ffi::GD_E_RECURSE_LEVEL => GdError::RecurseLevel(error_string),
+            ffi::GD_E_UNCLEAN_DB => GdError::UncleanDb(error_string),
+            //the encoding is filled in by the callers which know the fragment involved
+            ffi::GD_E_UNKNOWN_ENCODING | ffi::GD_E_UNSUPPORTED => {
+                GdError::UnsupportedEncoding(Encoding::Unknown, error_string)
+            }
             _ => {
                 panic!("Unsupported error");
             }
-        }
+        };
+        Some(error)
+    }
+
+    /// `get_error` for the call site of a failed call, which reports the error through
+    /// `log`/`tracing` (once, unlike `get_error` which can be called any number of times)
+    pub(crate) fn last_error(&self) -> Option<GdError> {
+        let error = self.get_error()?;
+        report_error!(self, error);
+        Some(error)
     }
 }
\ No newline at end of file
@@ -0,0 +1,107 @@
+use crate::ffi;
+use crate::GdError;
+
+/// Every data type known to GetData
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GdTypes {
+    Null,
+    Uint8,
+    Int8,
+    Uint16,
+    Int16,
+    Uint32,
+    Int32,
+    Uint64,
+    Int64,
+    Float32,
+    Float64,
+    Complex64,
+    Complex128,
+    String,
+}
+
+impl From<GdTypes> for ffi::gd_type_t {
+    fn from(gd_type: GdTypes) -> Self {
+        match gd_type {
+            GdTypes::Null => ffi::gd_type_t_GD_NULL,
+            GdTypes::Uint8 => ffi::gd_type_t_GD_UINT8,
+            GdTypes::Int8 => ffi::gd_type_t_GD_INT8,
+            GdTypes::Uint16 => ffi::gd_type_t_GD_UINT16,
+            GdTypes::Int16 => ffi::gd_type_t_GD_INT16,
+            GdTypes::Uint32 => ffi::gd_type_t_GD_UINT32,
+            GdTypes::Int32 => ffi::gd_type_t_GD_INT32,
+            GdTypes::Uint64 => ffi::gd_type_t_GD_UINT64,
+            GdTypes::Int64 => ffi::gd_type_t_GD_INT64,
+            GdTypes::Float32 => ffi::gd_type_t_GD_FLOAT32,
+            GdTypes::Float64 => ffi::gd_type_t_GD_FLOAT64,
+            GdTypes::Complex64 => ffi::gd_type_t_GD_COMPLEX64,
+            GdTypes::Complex128 => ffi::gd_type_t_GD_COMPLEX128,
+            GdTypes::String => ffi::gd_type_t_GD_STRING,
+        }
+    }
+}
+
+impl TryFrom<ffi::gd_type_t> for GdTypes {
+    type Error = GdError;
+    fn try_from(gd_type: ffi::gd_type_t) -> Result<Self, Self::Error> {
+        match gd_type {
+            ffi::gd_type_t_GD_NULL => Ok(GdTypes::Null),
+            ffi::gd_type_t_GD_UINT8 => Ok(GdTypes::Uint8),
+            ffi::gd_type_t_GD_INT8 => Ok(GdTypes::Int8),
+            ffi::gd_type_t_GD_UINT16 => Ok(GdTypes::Uint16),
+            ffi::gd_type_t_GD_INT16 => Ok(GdTypes::Int16),
+            ffi::gd_type_t_GD_UINT32 => Ok(GdTypes::Uint32),
+            ffi::gd_type_t_GD_INT32 => Ok(GdTypes::Int32),
+            ffi::gd_type_t_GD_UINT64 => Ok(GdTypes::Uint64),
+            ffi::gd_type_t_GD_INT64 => Ok(GdTypes::Int64),
+            ffi::gd_type_t_GD_FLOAT32 => Ok(GdTypes::Float32),
+            ffi::gd_type_t_GD_FLOAT64 => Ok(GdTypes::Float64),
+            ffi::gd_type_t_GD_COMPLEX64 => Ok(GdTypes::Complex64),
+            ffi::gd_type_t_GD_COMPLEX128 => Ok(GdTypes::Complex128),
+            ffi::gd_type_t_GD_STRING => Ok(GdTypes::String),
+            _ => Err(GdError::BadType(format!(
+                "Unknown data type: {:#x}",
+                gd_type
+            ))),
+        }
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Rust types which can be passed to and from GetData without conversion on our side.
+///
+/// Complex types are represented as `[re, im]` pairs which share the layout of C's
+/// `float complex` and `double complex`. This trait is sealed, it can not be implemented
+/// outside of this crate.
+pub trait GdNative: sealed::Sealed + Copy + Default + 'static {
+    const GD_TYPE: GdTypes;
+}
+
+macro_rules! impl_gd_native {
+    ($($rust_type:ty => $gd_type:expr),* $(,)?) => {
+        $(
+            impl sealed::Sealed for $rust_type {}
+            impl GdNative for $rust_type {
+                const GD_TYPE: GdTypes = $gd_type;
+            }
+        )*
+    };
+}
+
+impl_gd_native!(
+    u8 => GdTypes::Uint8,
+    i8 => GdTypes::Int8,
+    u16 => GdTypes::Uint16,
+    i16 => GdTypes::Int16,
+    u32 => GdTypes::Uint32,
+    i32 => GdTypes::Int32,
+    u64 => GdTypes::Uint64,
+    i64 => GdTypes::Int64,
+    f32 => GdTypes::Float32,
+    f64 => GdTypes::Float64,
+    [f32; 2] => GdTypes::Complex64,
+    [f64; 2] => GdTypes::Complex128,
+);
@@ -0,0 +1,78 @@
+use crate::ffi;
+use crate::{take_string, GdError, GdTypes};
+use std::ffi::{CStr, CString};
+use std::path::PathBuf;
+
+/// Size, position and type of fields
+impl crate::Dirfile {
+    /// the number of frames in the dirfile, the length of its reference field
+    pub fn nframes(&self) -> Result<u64, GdError> {
+        debug_call!(self, "nframes");
+        let nframes = unsafe { ffi::gd_nframes(self.dirfile.as_ptr()) };
+        match self.last_error() {
+            Some(error) => Err(error),
+            None => Ok(nframes.max(0) as u64),
+        }
+    }
+
+    /// the sample number of the first sample of a field (which can be non-zero because of
+    /// frame offsets or PHASE shifts)
+    pub fn bof(&self, field_code: &str) -> Result<u64, GdError> {
+        debug_call!(self, "bof", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let bof = unsafe { ffi::gd_bof(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        self.position(&field_code, bof)
+    }
+
+    /// the sample number one past the last sample of a field
+    pub fn eof(&self, field_code: &str) -> Result<u64, GdError> {
+        debug_call!(self, "eof", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let eof = unsafe { ffi::gd_eof(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        self.position(&field_code, eof)
+    }
+
+    /// GetData reports errors from its `off_t` functions with negative values
+    fn position(&self, field_code: &CStr, position: ffi::off_t) -> Result<u64, GdError> {
+        if let Some(error) = self.field_error(field_code) {
+            return Err(error);
+        }
+        Ok(position.max(0) as u64)
+    }
+
+    /// samples per frame of any vector field
+    pub fn spf(&self, field_code: &str) -> Result<u32, GdError> {
+        debug_call!(self, "spf", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let spf = unsafe { ffi::gd_spf(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if spf == 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(spf)
+    }
+
+    /// the type GetData would return the data of a field in without conversion, for derived
+    /// fields this is worked out from their inputs
+    pub fn native_type(&self, field_code: &str) -> Result<GdTypes, GdError> {
+        debug_call!(self, "native_type", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let native_type =
+            unsafe { ffi::gd_native_type(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if let Some(error) = self.last_error() {
+            return Err(error);
+        }
+        GdTypes::try_from(native_type)
+    }
+
+    /// path of the file holding the data of a RAW field
+    pub fn raw_filename(&self, field_code: &str) -> Result<PathBuf, GdError> {
+        debug_call!(self, "raw_filename", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let filename_ptr =
+            unsafe { ffi::gd_raw_filename(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if filename_ptr.is_null() {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(PathBuf::from(unsafe { take_string(filename_ptr) }))
+    }
+}
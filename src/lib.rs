@@ -4,127 +4,266 @@ pub mod ffi {
     #![allow(non_snake_case)]
     include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 }
+// reporting through `log`/`tracing`, first so the macros are visible in every module
+#[macro_use]
+mod diagnostics;
 // basic tests to confirm that getdata is working as expected
 #[cfg(test)]
 mod tests;
 
+mod alias;
+
+mod desync;
+
+pub use desync::DesyncOptions;
+
 mod entry;
 
-pub use entry::{Entry, EntryType};
+pub use entry::{
+    BinaryData, BitData, CarrayData, ConstData, Entry, EntryKind, EntryType, LincomData, LinterpData,
+    MplexData, PhaseData, PolynomData, RawData, RecipData, SarrayData, Scalar, WindowData,
+    WindowOp,
+};
+
+
+mod entry_edit;
+
+pub use entry_edit::{DeleteOptions, RenameOptions};
+
+mod entry_list;
 
+pub use entry_list::EntryFilter;
+
+mod field;
+
+pub use field::Field;
+
+mod fragment;
+
+pub use fragment::{Fragment, Protection};
 
 mod gd_error;
 
 pub use gd_error::GdError;
 
-use std::any::TypeId;
+mod gd_types;
+
+mod encoding;
+
+pub use encoding::{Encoding, EncodingSupport, Endianness};
+
+mod geometry;
+
+mod open_options;
+
+pub use open_options::OpenOptions;
+
+mod parser_callback;
+
+pub use parser_callback::{SyntaxAction, SyntaxError, SyntaxErrorKind};
+
+mod scalar_io;
+
+mod spec;
+
+pub use gd_types::{GdNative, GdTypes};
 
 use std::ffi::CString;
-use std::any::Any;
 
 
 //lets make a struct to hold the dirfile
+//
+//the handle is always open: it is closed by consuming the Dirfile (`close`/`discard`)
+//or when it is dropped
+//
+//only calls that change the metadata take `&mut self`. Data I/O (`putdata`, `seek`,
+//`flush`, ...) takes `&self` like `Field` does: GetData updates the I/O pointers and
+//open files behind the handle, which is fine as `NonNull` keeps the Dirfile `!Sync`
 pub struct Dirfile {
-    dirfile: Option<std::ptr::NonNull<ffi::DIRFILE>>,
+    dirfile: std::ptr::NonNull<ffi::DIRFILE>,
+    //GetData keeps a pointer to the parser callback, it must live as long as the handle
+    callback: Option<Box<parser_callback::ParserCallback>>,
+    //bumped every time `desync` reloads the metadata
+    generation: u64,
+    //path as passed to `open`, for `log`/`tracing` reports
+    #[cfg(any(feature = "log", feature = "tracing"))]
+    name: Option<String>,
 }
 
 
-#[derive(Clone, Copy)]
-pub enum GdTypes {
-    Float32,
-    Float64,
-    Int32,
-    Int64,
-    Uint32,
-    Uint64,
+pub enum FieldOrEntry {
+    Field(String),
+    Entry(Entry),
 }
 
-impl From<GdTypes> for ffi::gd_type_t {
-    fn from(gd_type: GdTypes) -> Self {
-        match gd_type {
-            GdTypes::Float32 => ffi::gd_type_t_GD_FLOAT32,
-            GdTypes::Float64 => ffi::gd_type_t_GD_FLOAT64,
-            GdTypes::Int32 => ffi::gd_type_t_GD_INT32,
-            GdTypes::Int64 => ffi::gd_type_t_GD_INT64,
-            GdTypes::Uint32 => ffi::gd_type_t_GD_UINT32,
-            GdTypes::Uint64 => ffi::gd_type_t_GD_UINT64,
+impl FieldOrEntry {
+    #[cfg(any(feature = "log", feature = "tracing"))]
+    fn field_code(&self) -> &str {
+        match self {
+            FieldOrEntry::Field(field_code) => field_code,
+            FieldOrEntry::Entry(entry) => entry.get_field_code(),
         }
     }
 }
 
-impl From<ffi::gd_type_t> for GdTypes {
-    fn from(gd_type: ffi::gd_type_t) -> Self {
-        match gd_type {
-            ffi::gd_type_t_GD_FLOAT32 => GdTypes::Float32,
-            ffi::gd_type_t_GD_FLOAT64 => GdTypes::Float64,
-            ffi::gd_type_t_GD_INT32 => GdTypes::Int32,
-            ffi::gd_type_t_GD_INT64 => GdTypes::Int64,
-            ffi::gd_type_t_GD_UINT32 => GdTypes::Uint32,
-            ffi::gd_type_t_GD_UINT64 => GdTypes::Uint64,
-            _ => {
-                panic!("Unsupported type");
-            }
-        }
+/// GetData can only back-compute raw values through these field types, some of them have
+/// further restrictions (eg. LINCOM with one input field) which it checks itself
+fn invertible(field_type: ffi::gd_entype_t) -> bool {
+    matches!(
+        field_type,
+        ffi::gd_entype_t_GD_RAW_ENTRY
+            | ffi::gd_entype_t_GD_LINCOM_ENTRY
+            | ffi::gd_entype_t_GD_LINTERP_ENTRY
+            | ffi::gd_entype_t_GD_BIT_ENTRY
+            | ffi::gd_entype_t_GD_SBIT_ENTRY
+            | ffi::gd_entype_t_GD_PHASE_ENTRY
+            | ffi::gd_entype_t_GD_POLYNOM_ENTRY
+            | ffi::gd_entype_t_GD_RECIP_ENTRY
+            | ffi::gd_entype_t_GD_MPLEX_ENTRY
+    )
+}
+
+/// copies a string malloc'ed by GetData and gives it back with free, null is treated as
+/// empty
+///
+/// # Safety
+/// `ptr` must be null or a nul terminated string allocated with malloc, it is invalid once
+/// this returns.
+unsafe fn take_string(ptr: *mut std::os::raw::c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
     }
+    let value = std::ffi::CStr::from_ptr(ptr)
+        .to_string_lossy()
+        .into_owned();
+    libc::free(ptr as *mut libc::c_void);
+    value
 }
 
-impl From<GdTypes> for TypeId {
-    fn from(gd_type: GdTypes) -> Self {
-        match gd_type {
-            GdTypes::Float32 => TypeId::of::<f32>(),
-            GdTypes::Float64 => TypeId::of::<f64>(),
-            GdTypes::Int32 => TypeId::of::<i32>(),
-            GdTypes::Int64 => TypeId::of::<i64>(),
-            GdTypes::Uint32 => TypeId::of::<u32>(),
-            GdTypes::Uint64 => TypeId::of::<u64>(),
+/// copies a null terminated list of strings, eg. from `gd_entry_list`
+///
+/// # Safety
+/// `list` must be a valid null terminated array of nul terminated strings. Such lists belong
+/// to GetData and are only valid until the next call on the dirfile, so copy them right away.
+unsafe fn copy_string_list(list: *const *const std::os::raw::c_char) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut i = 0;
+    loop {
+        let string = *list.add(i);
+        if string.is_null() {
+            break;
         }
+        strings.push(
+            std::ffi::CStr::from_ptr(string)
+                .to_string_lossy()
+                .into_owned(),
+        );
+        i += 1;
     }
+    strings
 }
 
+/// Reference point for `Dirfile::seek`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Whence {
+    /// from the beginning of the dirfile (`GD_SEEK_SET`)
+    Set,
+    /// from the current I/O pointer (`GD_SEEK_CUR`)
+    Cur,
+    /// from the end of the field (`GD_SEEK_END`)
+    End,
+}
 
-
-pub enum FieldOrEntry {
-    Field(String),
-    Entry(Entry),
+impl From<Whence> for std::os::raw::c_int {
+    fn from(whence: Whence) -> Self {
+        let whence = match whence {
+            Whence::Set => ffi::GD_SEEK_SET,
+            Whence::Cur => ffi::GD_SEEK_CUR,
+            Whence::End => ffi::GD_SEEK_END,
+        };
+        whence as std::os::raw::c_int
+    }
 }
 
 impl Dirfile {
     /// Open a dirfile in read/write mode, creating it if it does not exist
+    ///
+    /// Use `OpenOptions` for any other combination of flags.
     pub fn open(dirfile_name: &str) -> Result<Dirfile, GdError> {
-        let dirfile_name = CString::new(dirfile_name).unwrap();
-        let dirfile =
-            unsafe { ffi::gd_open(dirfile_name.as_ptr(), (ffi::GD_RDWR | ffi::GD_CREAT).into()) };
-        let df = Dirfile {
-            dirfile: std::ptr::NonNull::new(dirfile),
+        OpenOptions::new()
+            .read_write(true)
+            .create(true)
+            .open(dirfile_name)
+    }
+    /// Flush all changes to disk and close the dirfile
+    ///
+    /// If the flush fails the changes which could not be written are discarded, the
+    /// dirfile is closed anyway and the error is returned. Dropping a `Dirfile` closes it
+    /// the same way but ignores the error.
+    pub fn close(self) -> Result<(), GdError> {
+        debug_call!(self, "close");
+        //the handle is released here, not in drop
+        let mut dirfile = std::mem::ManuallyDrop::new(self);
+        let _callback = dirfile.callback.take();
+        let ret_val = unsafe { ffi::gd_close(dirfile.dirfile.as_ptr()) };
+        let result = if ret_val != 0 {
+            let error = dirfile.last_error().unwrap();
+            unsafe { ffi::gd_discard(dirfile.dirfile.as_ptr()) };
+            Err(error)
+        } else {
+            Ok(())
         };
-        match df.get_error() {
-            None => Ok(Dirfile {
-                dirfile: std::ptr::NonNull::new(dirfile),
-            }),
-            Some(error) => {
-                unsafe { ffi::gd_close(dirfile) };
-                Err(error)
-            }
-        }
+        //kept for the report above, dropped by hand as the rest of `self`
+        #[cfg(any(feature = "log", feature = "tracing"))]
+        drop(dirfile.name.take());
+        result
     }
-    /// Close the dirfile
-    pub fn close(&mut self) {
-        unsafe { ffi::gd_close(self.dirfile.expect("open dirfile!").as_ptr()) };
-        self.dirfile = None;
+
+    /// Close the dirfile without writing any pending changes to disk
+    ///
+    /// Data and metadata which were already flushed stay on disk.
+    pub fn discard(self) -> Result<(), GdError> {
+        debug_call!(self, "discard");
+        let mut dirfile = std::mem::ManuallyDrop::new(self);
+        let _callback = dirfile.callback.take();
+        let ret_val = unsafe { ffi::gd_discard(dirfile.dirfile.as_ptr()) };
+        let result = if ret_val != 0 {
+            Err(dirfile.last_error().unwrap())
+        } else {
+            Ok(())
+        };
+        #[cfg(any(feature = "log", feature = "tracing"))]
+        drop(dirfile.name.take());
+        result
     }
 
     /// add entry
     pub fn add(&mut self, entry: &Entry) -> Result<(), GdError> {
-        let ret_val = unsafe {
-            ffi::gd_add(
-                self.dirfile.expect("Open the dirfile!").as_ptr(),
-                &entry.entry_c,
-            )
-        };
+        debug_call!(self, "add", field_code = entry.get_field_code());
+        let entry_c = entry.to_c()?;
+        let ret_val = unsafe { ffi::gd_add(self.dirfile.as_ptr(), &entry_c) };
         if ret_val == 0 {
             Ok(())
         } else {
-            Err(self.get_error().unwrap())
+            Err(self.last_error().unwrap())
+        }
+    }
+
+    /// add `entry` as a metafield of `parent`, the field code of `entry` is the name of
+    /// the metafield without the parent, eg. `units` for `temp/units`
+    ///
+    /// The metafield goes in the fragment of its parent. Adding an entry with the full
+    /// field code (`temp/units`) with `add` does the same.
+    pub fn madd(&mut self, entry: &Entry, parent: &str) -> Result<(), GdError> {
+        debug_call!(self, "madd", field_code = entry.get_field_code(), parent);
+        let entry_c = entry.to_c()?;
+        let parent = CString::new(parent).unwrap();
+        let ret_val =
+            unsafe { ffi::gd_madd(self.dirfile.as_ptr(), &entry_c, parent.as_ptr()) };
+        if ret_val == 0 {
+            Ok(())
+        } else {
+            Err(self.last_error().unwrap())
         }
     }
 
@@ -133,6 +272,7 @@ impl Dirfile {
         alias_name: &str,
         field_or_entry: FieldOrEntry,
     ) -> Result<(), GdError> {
+        debug_call!(self, "add_alias", alias_name, target = field_or_entry.field_code());
         let alias_name = CString::new(alias_name).unwrap();
 
         let field_code_c = match field_or_entry {
@@ -142,7 +282,7 @@ impl Dirfile {
 
         let ret_val = unsafe {
             ffi::gd_add_alias(
-                self.dirfile.expect("Open the dirfile!").as_ptr(),
+                self.dirfile.as_ptr(),
                 alias_name.as_ptr(),
                 field_code_c.as_ptr(),
                 0,
@@ -151,10 +291,13 @@ impl Dirfile {
         if ret_val == 0 {
             Ok(())
         } else {
-            Err(self.get_error().unwrap())
+            Err(self.last_error().unwrap())
         }
     }
+    /// the entry of a field, an alias resolves to the entry of its target (see
+    /// `alias_target` to tell them apart)
     pub fn get_entry(&self, field_code: &str) -> Result<Entry, GdError> {
+        debug_call!(self, "get_entry", field_code);
         let field_code = CString::new(field_code).unwrap();
         let mut entry_c: ffi::gd_entry_t;
         unsafe {
@@ -162,91 +305,257 @@ impl Dirfile {
         }
         let ret_val = unsafe {
             ffi::gd_entry(
-                self.dirfile.expect("Open the dirfile!").as_ptr(),
+                self.dirfile.as_ptr(),
                 field_code.as_ptr(),
                 &mut entry_c,
             )
         };
         if ret_val != 0 {
-            return Err(self.get_error().unwrap());
+            return Err(self.last_error().unwrap());
         }
 
-        let entry = Entry::from_c(field_code.to_str().unwrap(), entry_c);
+        let entry = unsafe { Entry::from_c(entry_c) };
         Ok(entry)
     }
 
-    /// puts data vectors, returns if the write was successful
-    pub fn putdata<T: 'static>(
-        &mut self,
+    /// puts data vectors at the current I/O pointer, returns the number of samples written
+    ///
+    /// GetData converts from `T` to the type of the field on disk.
+    pub fn putdata<T: GdNative>(
+        &self,
         field_or_entry: FieldOrEntry,
-        data: &Vec<T>,
+        data: &[T],
     ) -> Result<usize, GdError> {
-        let entry = match field_or_entry {
-            FieldOrEntry::Field(field_code) => self.get_entry(&field_code).unwrap(),
-            FieldOrEntry::Entry(entry) => entry,
-        };
-        match entry.field_type {
-            //only raw data is supported for now
-            EntryType::Raw( raw_data) => {
-                //check that the type is correct
-                let gd_type = GdTypes::from(raw_data.gd_type);
-                assert_eq!(
-                    TypeId::from(gd_type),
-                    TypeId::of::<T>(),
-                    "Data type mismatch"
-                );
-                //figure out how much data to write
-                let num_frames: usize = data.len() / raw_data.spf as usize;
-                let num_samples: usize = data.len() % raw_data.spf as usize;
-
-                //write data, will need to update the offset and stuff...
-                let write_n = unsafe {
-                    ffi::gd_putdata(
-                        self.dirfile.expect("Open the dirfile!").as_ptr(),
-                        entry.field.as_ptr() as *const i8,
-                        ffi::GD_HERE.into(),
-                        0,
-                        num_frames,
-                        num_samples,
-                        raw_data.gd_type,
-                        data.as_ptr() as *const std::ffi::c_void,
-                    )
-                };
-                if write_n != data.len() {
-                    match self.get_error() {
-                        Some(error) => return Err(error),
-                        None => {}
-                    };
+        self.putdata_at(field_or_entry, ffi::GD_HERE.into(), 0, data)
+    }
+
+    /// puts data vectors starting `first_sample` samples after the start of frame
+    /// `first_frame`, returns the number of samples written
+    ///
+    /// Writing before the end of the field overwrites the data already there, writing past
+    /// it pads the gap with zeros. The I/O pointer is left after the last sample written.
+    pub fn putdata_at<T: GdNative>(
+        &self,
+        field_or_entry: FieldOrEntry,
+        first_frame: i64,
+        first_sample: i64,
+        data: &[T],
+    ) -> Result<usize, GdError> {
+        debug_call!(
+            self,
+            "putdata_at",
+            field_code = field_or_entry.field_code(),
+            first_frame,
+            first_sample,
+            num_samples = data.len()
+        );
+        let dirfile = self.dirfile.as_ptr();
+        let (field_code, field_type) = match field_or_entry {
+            FieldOrEntry::Field(field_code) => {
+                let field_code = CString::new(field_code).unwrap();
+                let field_type = unsafe { ffi::gd_entry_type(dirfile, field_code.as_ptr()) };
+                if field_type == ffi::gd_entype_t_GD_NO_ENTRY {
+                    return Err(self.last_error().unwrap());
                 }
-                Ok(write_n)
+                (field_code, field_type)
             }
-            _ => {
-                panic!("only put data for RAW");
+            FieldOrEntry::Entry(entry) => (entry.field, entry.field_type.kind().into()),
+        };
+        if !invertible(field_type) {
+            return Err(GdError::BadFieldType(format!(
+                "Cannot write to non-invertible field: {}",
+                field_code.to_str().unwrap()
+            )));
+        }
+
+        let write_n = unsafe {
+            ffi::gd_putdata(
+                dirfile,
+                field_code.as_ptr(),
+                first_frame,
+                first_sample,
+                0,
+                data.len(),
+                T::GD_TYPE.into(),
+                data.as_ptr() as *const std::ffi::c_void,
+            )
+        };
+        if write_n != data.len() {
+            if let Some(error) = self.field_error(&field_code) {
+                return Err(error);
             }
         }
+        Ok(write_n)
     }
 
-    pub fn flush(&mut self) -> Result<(), GdError> {
-        let ret_val =
-            unsafe { ffi::gd_flush(self.dirfile.unwrap().as_ptr(), std::ptr::null_mut()) };
+    /// gets data vectors, returns the samples that were read
+    ///
+    /// Reads `num_frames` frames plus `num_samples` samples starting `first_sample` samples
+    /// after the start of frame `first_frame` (pass `ffi::GD_HERE` to continue from the
+    /// current I/O pointer). Works for derived fields as well as RAW, GetData converts
+    /// from the native type of the field to `T`. A read which runs into the end of the
+    /// field is not an error, the returned vector is just shorter than requested.
+    pub fn getdata<T: GdNative>(
+        &self,
+        field_or_entry: FieldOrEntry,
+        first_frame: i64,
+        first_sample: i64,
+        num_frames: usize,
+        num_samples: usize,
+    ) -> Result<Vec<T>, GdError> {
+        debug_call!(
+            self,
+            "getdata",
+            field_code = field_or_entry.field_code(),
+            first_frame,
+            first_sample,
+            num_frames,
+            num_samples
+        );
+        let field_code = match field_or_entry {
+            FieldOrEntry::Field(field_code) => CString::new(field_code).unwrap(),
+            FieldOrEntry::Entry(entry) => entry.field,
+        };
+        let dirfile = self.dirfile.as_ptr();
+
+        //work out how many samples were asked for to size the buffer
+        let spf = unsafe { ffi::gd_spf(dirfile, field_code.as_ptr()) };
+        if spf == 0 {
+            return Err(self.last_error().unwrap());
+        }
+        let num_requested = num_frames * spf as usize + num_samples;
+        let mut data: Vec<T> = vec![T::default(); num_requested];
+
+        let read_n = unsafe {
+            ffi::gd_getdata(
+                dirfile,
+                field_code.as_ptr(),
+                first_frame,
+                first_sample,
+                num_frames,
+                num_samples,
+                T::GD_TYPE.into(),
+                data.as_mut_ptr() as *mut std::ffi::c_void,
+            )
+        };
+        if read_n != num_requested {
+            //a short read is either an error or the end of the field
+            if let Some(error) = self.field_error(&field_code) {
+                return Err(error);
+            }
+            data.truncate(read_n);
+        }
+        Ok(data)
+    }
+
+    /// moves the I/O pointer of a field, returns the new position in samples
+    ///
+    /// The position is `frame_num` frames plus `sample_num` samples relative to `whence`.
+    /// This never extends the field, use `seek_write` to position a write past its end.
+    pub fn seek(
+        &self,
+        field_code: &str,
+        frame_num: i64,
+        sample_num: i64,
+        whence: Whence,
+    ) -> Result<u64, GdError> {
+        debug_call!(self, "seek", field_code, frame_num, sample_num, whence);
+        self._seek(field_code, frame_num, sample_num, whence.into())
+    }
+
+    /// like `seek`, but seeking past the end of the field pads it with zeros so the next
+    /// `putdata` writes at the requested position
+    pub fn seek_write(
+        &self,
+        field_code: &str,
+        frame_num: i64,
+        sample_num: i64,
+        whence: Whence,
+    ) -> Result<u64, GdError> {
+        debug_call!(
+            self,
+            "seek_write",
+            field_code,
+            frame_num,
+            sample_num,
+            whence
+        );
+        let whence: std::os::raw::c_int = whence.into();
+        self._seek(
+            field_code,
+            frame_num,
+            sample_num,
+            whence | ffi::GD_SEEK_WRITE as std::os::raw::c_int,
+        )
+    }
+
+    fn _seek(
+        &self,
+        field_code: &str,
+        frame_num: i64,
+        sample_num: i64,
+        whence: std::os::raw::c_int,
+    ) -> Result<u64, GdError> {
+        let field_code = CString::new(field_code).unwrap();
+        let position = unsafe {
+            ffi::gd_seek(
+                self.dirfile.as_ptr(),
+                field_code.as_ptr(),
+                frame_num,
+                sample_num,
+                whence,
+            )
+        };
+        if position < 0 {
+            return Err(self.field_error(&field_code).unwrap());
+        }
+        Ok(position as u64)
+    }
+
+    /// the current position of the I/O pointer of a field in samples
+    pub fn tell(&self, field_code: &str) -> Result<u64, GdError> {
+        debug_call!(self, "tell", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let position = unsafe { ffi::gd_tell(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if position < 0 {
+            return Err(self.field_error(&field_code).unwrap());
+        }
+        Ok(position as u64)
+    }
+
+    pub fn flush(&self) -> Result<(), GdError> {
+        debug_call!(self, "flush");
+        let ret_val = unsafe { ffi::gd_flush(self.dirfile.as_ptr(), std::ptr::null_mut()) };
         if ret_val != 0 {
-            return Err(self.get_error().unwrap());
+            return Err(self.last_error().unwrap());
         }
         Ok(())
     }
-    pub fn sync(&mut self) -> Result<(), GdError> {
-        let ret_val = unsafe { ffi::gd_sync(self.dirfile.unwrap().as_ptr(), std::ptr::null_mut()) };
+    pub fn sync(&self) -> Result<(), GdError> {
+        debug_call!(self, "sync");
+        let ret_val = unsafe { ffi::gd_sync(self.dirfile.as_ptr(), std::ptr::null_mut()) };
         if ret_val != 0 {
-            return Err(self.get_error().unwrap());
+            return Err(self.last_error().unwrap());
         }
         Ok(())
     }
-    pub fn metaflush(&mut self) -> Result<(), GdError> {
-        let ret_val = unsafe { ffi::gd_metaflush(self.dirfile.unwrap().as_ptr()) };
+    pub fn metaflush(&self) -> Result<(), GdError> {
+        debug_call!(self, "metaflush");
+        let ret_val = unsafe { ffi::gd_metaflush(self.dirfile.as_ptr()) };
         if ret_val != 0 {
-            return Err(self.get_error().unwrap());
+            return Err(self.last_error().unwrap());
         }
         Ok(())
     }
+}
 
+impl Drop for Dirfile {
+    fn drop(&mut self) {
+        //there is nobody to report an error to, make sure the handle is released anyway
+        unsafe {
+            if ffi::gd_close(self.dirfile.as_ptr()) != 0 {
+                ffi::gd_discard(self.dirfile.as_ptr());
+            }
+        }
+    }
 }
@@ -0,0 +1,201 @@
+use crate::ffi;
+use crate::parser_callback::{parser_callback, ParserCallback};
+use crate::{Dirfile, Encoding, Endianness, GdError, SyntaxAction, SyntaxError};
+use std::ffi::CString;
+use std::os::raw::{c_int, c_ulong, c_void};
+use std::ptr::NonNull;
+
+/// Flags for opening a dirfile, eg.
+/// `OpenOptions::new().read_write(true).create(true).open("path")`.
+///
+/// The defaults open an existing dirfile read-only, exactly as found on disk.
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    read_write: bool,
+    create: bool,
+    exclusive: bool,
+    truncate: bool,
+    endianness: Option<Endianness>,
+    arm_endian: bool,
+    encoding: Option<Encoding>,
+    force_encoding: bool,
+    verbose: bool,
+    ignore_dups: bool,
+    pedantic: bool,
+    permissive: bool,
+    standards: Option<i32>,
+}
+
+impl OpenOptions {
+    pub fn new() -> OpenOptions {
+        OpenOptions::default()
+    }
+    /// open for writing as well as reading (`GD_RDWR`)
+    pub fn read_write(mut self, read_write: bool) -> OpenOptions {
+        self.read_write = read_write;
+        self
+    }
+    /// create the dirfile if it does not exist (`GD_CREAT`)
+    pub fn create(mut self, create: bool) -> OpenOptions {
+        self.create = create;
+        self
+    }
+    /// create the dirfile, failing if it already exists (`GD_CREAT | GD_EXCL`)
+    pub fn create_new(mut self, create_new: bool) -> OpenOptions {
+        self.create = create_new;
+        self.exclusive = create_new;
+        self
+    }
+    /// delete the contents of an existing dirfile (`GD_TRUNC`)
+    pub fn truncate(mut self, truncate: bool) -> OpenOptions {
+        self.truncate = truncate;
+        self
+    }
+    /// byte order assumed for RAW data, overriding any `/ENDIAN` directive
+    /// (`GD_FORCE_ENDIAN`)
+    pub fn endianness(mut self, endianness: Endianness) -> OpenOptions {
+        self.endianness = Some(endianness);
+        self
+    }
+    /// double precision floats are stored in ARM middle-endian order (`GD_ARM_ENDIAN`)
+    pub fn arm_endian(mut self, arm_endian: bool) -> OpenOptions {
+        self.arm_endian = arm_endian;
+        self
+    }
+    /// encoding of fragments which do not specify one, eg. in a newly created dirfile
+    pub fn encoding(mut self, encoding: Encoding) -> OpenOptions {
+        self.encoding = Some(encoding);
+        self
+    }
+    /// use `encoding` even for fragments with an `/ENCODING` directive
+    /// (`GD_FORCE_ENCODING`)
+    pub fn force_encoding(mut self, force_encoding: bool) -> OpenOptions {
+        self.force_encoding = force_encoding;
+        self
+    }
+    /// let GetData print its errors to stderr (`GD_VERBOSE`), the `log` and `tracing`
+    /// features report them as events instead
+    pub fn verbose(mut self, verbose: bool) -> OpenOptions {
+        self.verbose = verbose;
+        self
+    }
+    /// silently drop duplicate field definitions in the format files (`GD_IGNORE_DUPS`)
+    pub fn ignore_dups(mut self, ignore_dups: bool) -> OpenOptions {
+        self.ignore_dups = ignore_dups;
+        self
+    }
+    /// parse the format files strictly according to their Standards version
+    /// (`GD_PEDANTIC`)
+    pub fn pedantic(mut self, pedantic: bool) -> OpenOptions {
+        self.pedantic = pedantic;
+        self
+    }
+    /// accept syntax from any Standards version (`GD_PERMISSIVE`)
+    pub fn permissive(mut self, permissive: bool) -> OpenOptions {
+        self.permissive = permissive;
+        self
+    }
+    /// Standards version used when writing the metadata, see `gd_dirfile_standards`
+    pub fn standards(mut self, version: i32) -> OpenOptions {
+        self.standards = Some(version);
+        self
+    }
+
+    pub(crate) fn flags(&self) -> c_ulong {
+        let mut flags = if self.read_write {
+            ffi::GD_RDWR as c_ulong
+        } else {
+            ffi::GD_RDONLY as c_ulong
+        };
+        let mut set = |set: bool, flag: c_ulong| {
+            if set {
+                flags |= flag;
+            }
+        };
+        set(self.create, ffi::GD_CREAT as c_ulong);
+        set(self.exclusive, ffi::GD_EXCL as c_ulong);
+        set(self.truncate, ffi::GD_TRUNC as c_ulong);
+        set(self.arm_endian, ffi::GD_ARM_ENDIAN as c_ulong);
+        set(self.force_encoding, ffi::GD_FORCE_ENCODING as c_ulong);
+        set(self.verbose, ffi::GD_VERBOSE as c_ulong);
+        set(self.ignore_dups, ffi::GD_IGNORE_DUPS as c_ulong);
+        set(self.pedantic, ffi::GD_PEDANTIC as c_ulong);
+        set(self.permissive, ffi::GD_PERMISSIVE as c_ulong);
+        if let Some(endianness) = self.endianness {
+            flags |= ffi::GD_FORCE_ENDIAN as c_ulong | c_ulong::from(endianness);
+        }
+        if let Some(encoding) = self.encoding {
+            flags |= c_ulong::from(encoding);
+        }
+        flags
+    }
+
+    pub fn open(&self, dirfile_name: &str) -> Result<Dirfile, GdError> {
+        self._open(dirfile_name, None)
+    }
+
+    /// open the dirfile, calling `callback` for every format file line which cannot be
+    /// parsed
+    ///
+    /// The callback decides whether to abort, skip the line or parse a corrected one
+    /// instead. It is kept for the lifetime of the `Dirfile` as GetData also calls it when
+    /// fragments are included later on. If it panics the parse is aborted.
+    pub fn open_with_callback<F>(&self, dirfile_name: &str, callback: F) -> Result<Dirfile, GdError>
+    where
+        F: FnMut(&SyntaxError) -> SyntaxAction + 'static,
+    {
+        self._open(dirfile_name, Some(Box::new(Box::new(callback))))
+    }
+
+    fn _open(
+        &self,
+        dirfile_name: &str,
+        mut callback: Option<Box<ParserCallback>>,
+    ) -> Result<Dirfile, GdError> {
+        #[cfg(any(feature = "log", feature = "tracing"))]
+        let name = Some(dirfile_name.to_string());
+        let dirfile_name = CString::new(dirfile_name).unwrap();
+        let dirfile = match callback.as_mut() {
+            None => unsafe { ffi::gd_open(dirfile_name.as_ptr(), self.flags()) },
+            Some(callback) => unsafe {
+                ffi::gd_cbopen(
+                    dirfile_name.as_ptr(),
+                    self.flags(),
+                    Some(parser_callback),
+                    &mut **callback as *mut ParserCallback as *mut c_void,
+                )
+            },
+        };
+        let dirfile = NonNull::new(dirfile)
+            .ok_or_else(|| GdError::Alloc("Unable to allocate the dirfile".to_string()))?;
+        let df = Dirfile {
+            dirfile,
+            callback,
+            generation: 0,
+            #[cfg(any(feature = "log", feature = "tracing"))]
+            name,
+        };
+        debug_call!(df, "open", options = self, callback = df.callback.is_some());
+        if let Some(error) = df.get_error() {
+            let error = match (error, self.encoding) {
+                //opening only touches data files when creating them in this encoding
+                (GdError::UnsupportedEncoding(Encoding::Unknown, msg), Some(encoding)) => {
+                    GdError::UnsupportedEncoding(encoding, msg)
+                }
+                (error, _) => error,
+            };
+            report_error!(df, error);
+            let _ = df.discard();
+            return Err(error);
+        }
+        if let Some(version) = self.standards {
+            let ret_val = unsafe { ffi::gd_dirfile_standards(dirfile.as_ptr(), version as c_int) };
+            if ret_val < 0 {
+                let error = df.last_error().unwrap();
+                let _ = df.discard();
+                return Err(error);
+            }
+        }
+        Ok(df)
+    }
+}
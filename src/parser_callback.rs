@@ -0,0 +1,171 @@
+use crate::ffi;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+/// What went wrong on a format file line, the `suberror` GetData passes to the parser
+/// callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxErrorKind {
+    /// bad samples-per-frame of a RAW field (`GD_E_FORMAT_BAD_SPF`)
+    BadSpf,
+    /// wrong number of input fields (`GD_E_FORMAT_N_FIELDS`)
+    NFields,
+    /// not enough tokens on the line (`GD_E_FORMAT_N_TOK`)
+    NTok,
+    /// bad number of bits of a BIT or SBIT field (`GD_E_FORMAT_NUMBITS`)
+    Numbits,
+    /// bad first bit of a BIT or SBIT field (`GD_E_FORMAT_BITNUM`)
+    Bitnum,
+    /// the bits of a BIT or SBIT field run past 64 (`GD_E_FORMAT_BITSIZE`)
+    Bitsize,
+    /// invalid character (`GD_E_FORMAT_CHARACTER`)
+    Character,
+    /// unrecognised line (`GD_E_FORMAT_BAD_LINE`)
+    BadLine,
+    /// reserved field name (`GD_E_FORMAT_RES_NAME`)
+    ResName,
+    /// unknown byte order in `/ENDIAN` (`GD_E_FORMAT_ENDIAN`)
+    Endian,
+    /// unknown data type (`GD_E_FORMAT_BAD_TYPE`)
+    BadType,
+    /// invalid field name (`GD_E_FORMAT_BAD_NAME`)
+    BadName,
+    /// unterminated token (`GD_E_FORMAT_UNTERM`)
+    Unterm,
+    /// metafield defined with a RAW parent (`GD_E_FORMAT_METARAW`)
+    Metaraw,
+    /// metafield of a parent which does not exist (`GD_E_FORMAT_NO_FIELD`)
+    NoField,
+    /// field name already defined (`GD_E_FORMAT_DUPLICATE`)
+    Duplicate,
+    /// metafield defined in a different fragment than its parent (`GD_E_FORMAT_LOCATION`)
+    Location,
+    /// bad protection level in `/PROTECT` (`GD_E_FORMAT_PROTECT`)
+    Protect,
+    /// unexpected literal (`GD_E_FORMAT_LITERAL`)
+    Literal,
+    /// unknown WINDOW operator (`GD_E_FORMAT_WINDOP`)
+    Windop,
+    /// metafield of a metafield (`GD_E_FORMAT_META_META`)
+    MetaMeta,
+    /// bad use of an alias (`GD_E_FORMAT_ALIAS`)
+    Alias,
+    /// bad MPLEX count value (`GD_E_FORMAT_MPLEXVAL`)
+    Mplexval,
+    /// a suberror this crate does not know about
+    Other(i32),
+}
+
+impl From<c_int> for SyntaxErrorKind {
+    fn from(suberror: c_int) -> Self {
+        match suberror as u32 {
+            ffi::GD_E_FORMAT_BAD_SPF => SyntaxErrorKind::BadSpf,
+            ffi::GD_E_FORMAT_N_FIELDS => SyntaxErrorKind::NFields,
+            ffi::GD_E_FORMAT_N_TOK => SyntaxErrorKind::NTok,
+            ffi::GD_E_FORMAT_NUMBITS => SyntaxErrorKind::Numbits,
+            ffi::GD_E_FORMAT_BITNUM => SyntaxErrorKind::Bitnum,
+            ffi::GD_E_FORMAT_BITSIZE => SyntaxErrorKind::Bitsize,
+            ffi::GD_E_FORMAT_CHARACTER => SyntaxErrorKind::Character,
+            ffi::GD_E_FORMAT_BAD_LINE => SyntaxErrorKind::BadLine,
+            ffi::GD_E_FORMAT_RES_NAME => SyntaxErrorKind::ResName,
+            ffi::GD_E_FORMAT_ENDIAN => SyntaxErrorKind::Endian,
+            ffi::GD_E_FORMAT_BAD_TYPE => SyntaxErrorKind::BadType,
+            ffi::GD_E_FORMAT_BAD_NAME => SyntaxErrorKind::BadName,
+            ffi::GD_E_FORMAT_UNTERM => SyntaxErrorKind::Unterm,
+            ffi::GD_E_FORMAT_METARAW => SyntaxErrorKind::Metaraw,
+            ffi::GD_E_FORMAT_NO_FIELD => SyntaxErrorKind::NoField,
+            ffi::GD_E_FORMAT_DUPLICATE => SyntaxErrorKind::Duplicate,
+            ffi::GD_E_FORMAT_LOCATION => SyntaxErrorKind::Location,
+            ffi::GD_E_FORMAT_PROTECT => SyntaxErrorKind::Protect,
+            ffi::GD_E_FORMAT_LITERAL => SyntaxErrorKind::Literal,
+            ffi::GD_E_FORMAT_WINDOP => SyntaxErrorKind::Windop,
+            ffi::GD_E_FORMAT_META_META => SyntaxErrorKind::MetaMeta,
+            ffi::GD_E_FORMAT_ALIAS => SyntaxErrorKind::Alias,
+            ffi::GD_E_FORMAT_MPLEXVAL => SyntaxErrorKind::Mplexval,
+            _ => SyntaxErrorKind::Other(suberror),
+        }
+    }
+}
+
+/// A format file line GetData could not parse, as passed to the parser callback
+#[derive(Debug)]
+pub struct SyntaxError<'a> {
+    /// line number in the fragment, starting from 1
+    pub line_num: usize,
+    /// path of the fragment
+    pub fragment: &'a Path,
+    pub kind: SyntaxErrorKind,
+    /// the offending line
+    pub line: &'a str,
+}
+
+/// What the parser should do about a `SyntaxError`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxAction {
+    /// stop parsing, opening the dirfile fails with `GdError::Format` (`GD_SYNTAX_ABORT`)
+    Abort,
+    /// skip the line and carry on (`GD_SYNTAX_IGNORE`)
+    Ignore,
+    /// parse the line again, the callback is called again if it still fails
+    /// (`GD_SYNTAX_RESCAN`)
+    Rescan,
+    /// parse this line in place of the offending one
+    Substitute(String),
+}
+
+pub(crate) type ParserCallback = Box<dyn FnMut(&SyntaxError) -> SyntaxAction>;
+
+/// `gd_parser_callback_t` which forwards to the `ParserCallback` passed as `extra`
+///
+/// A panic in the callback is caught here and aborts the parse.
+pub(crate) unsafe extern "C" fn parser_callback(
+    pdata: *mut ffi::gd_parser_data_t,
+    extra: *mut c_void,
+) -> c_int {
+    let callback = &mut *(extra as *mut ParserCallback);
+    let pdata = &mut *pdata;
+    let fragment = CStr::from_ptr(pdata.filename).to_string_lossy();
+    //copied, the buffer may be overwritten by a substitution below
+    let line = CStr::from_ptr(pdata.line).to_string_lossy().into_owned();
+    let error = SyntaxError {
+        line_num: pdata.linenum as usize,
+        fragment: Path::new(&*fragment),
+        kind: SyntaxErrorKind::from(pdata.suberror),
+        line: &line,
+    };
+    let action = panic::catch_unwind(AssertUnwindSafe(|| callback(&error)));
+    let action = match action {
+        Ok(action) => action,
+        Err(_) => SyntaxAction::Abort,
+    };
+    let action = match action {
+        SyntaxAction::Abort => ffi::GD_SYNTAX_ABORT,
+        SyntaxAction::Ignore => ffi::GD_SYNTAX_IGNORE,
+        SyntaxAction::Rescan => ffi::GD_SYNTAX_RESCAN,
+        SyntaxAction::Substitute(new_line) => {
+            let new_line = match CString::new(new_line) {
+                Ok(new_line) => new_line,
+                Err(_) => return ffi::GD_SYNTAX_ABORT as c_int,
+            };
+            let new_line = new_line.as_bytes_with_nul();
+            //the line buffer belongs to GetData, grow it with realloc if needed
+            if new_line.len() > pdata.buflen {
+                let buffer = libc::realloc(pdata.line as *mut libc::c_void, new_line.len());
+                if buffer.is_null() {
+                    return ffi::GD_SYNTAX_ABORT as c_int;
+                }
+                pdata.line = buffer as *mut c_char;
+                pdata.buflen = new_line.len();
+            }
+            std::ptr::copy_nonoverlapping(
+                new_line.as_ptr() as *const c_char,
+                pdata.line,
+                new_line.len(),
+            );
+            ffi::GD_SYNTAX_RESCAN
+        }
+    };
+    action as c_int
+}
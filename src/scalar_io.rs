@@ -0,0 +1,271 @@
+use crate::ffi;
+use crate::{EntryFilter, EntryKind, GdError, GdNative};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+/// Reading and writing the values of scalar fields (CONST, CARRAY, STRING and SARRAY).
+///
+/// As with `getdata`/`putdata` GetData converts between `T` and the type of the field.
+impl crate::Dirfile {
+    pub fn get_constant<T: GdNative>(&self, field_code: &str) -> Result<T, GdError> {
+        debug_call!(self, "get_constant", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let mut value = T::default();
+        let ret_val = unsafe {
+            ffi::gd_get_constant(
+                self.dirfile.as_ptr(),
+                field_code.as_ptr(),
+                T::GD_TYPE.into(),
+                &mut value as *mut T as *mut std::ffi::c_void,
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(value)
+    }
+
+    pub fn put_constant<T: GdNative>(&mut self, field_code: &str, value: T) -> Result<(), GdError> {
+        debug_call!(self, "put_constant", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let ret_val = unsafe {
+            ffi::gd_put_constant(
+                self.dirfile.as_ptr(),
+                field_code.as_ptr(),
+                T::GD_TYPE.into(),
+                &value as *const T as *const std::ffi::c_void,
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    /// number of elements of a CARRAY or SARRAY (1 for CONST and STRING)
+    pub fn array_len(&self, field_code: &str) -> Result<usize, GdError> {
+        debug_call!(self, "array_len", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let len = unsafe { ffi::gd_array_len(self.dirfile.as_ptr(), field_code.as_ptr()) };
+        if len == 0 {
+            if let Some(error) = self.last_error() {
+                return Err(error);
+            }
+        }
+        Ok(len)
+    }
+
+    pub fn get_carray<T: GdNative>(&self, field_code: &str) -> Result<Vec<T>, GdError> {
+        let len = self.array_len(field_code)?;
+        self.get_carray_slice(field_code, 0, len)
+    }
+
+    /// `n` elements of a CARRAY starting from element `start`
+    pub fn get_carray_slice<T: GdNative>(
+        &self,
+        field_code: &str,
+        start: usize,
+        n: usize,
+    ) -> Result<Vec<T>, GdError> {
+        debug_call!(self, "get_carray_slice", field_code, start, n);
+        let field_code = CString::new(field_code).unwrap();
+        let mut data: Vec<T> = vec![T::default(); n];
+        let ret_val = unsafe {
+            ffi::gd_get_carray_slice(
+                self.dirfile.as_ptr(),
+                field_code.as_ptr(),
+                start as _,
+                n,
+                T::GD_TYPE.into(),
+                data.as_mut_ptr() as *mut std::ffi::c_void,
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(data)
+    }
+
+    /// overwrites the elements of a CARRAY starting from the first one
+    pub fn put_carray<T: GdNative>(&mut self, field_code: &str, data: &[T]) -> Result<(), GdError> {
+        self.put_carray_slice(field_code, 0, data)
+    }
+
+    /// overwrites the elements of a CARRAY starting from element `start`
+    pub fn put_carray_slice<T: GdNative>(
+        &mut self,
+        field_code: &str,
+        start: usize,
+        data: &[T],
+    ) -> Result<(), GdError> {
+        debug_call!(self, "put_carray_slice", field_code, start);
+        let field_code = CString::new(field_code).unwrap();
+        let ret_val = unsafe {
+            ffi::gd_put_carray_slice(
+                self.dirfile.as_ptr(),
+                field_code.as_ptr(),
+                start as _,
+                data.len(),
+                T::GD_TYPE.into(),
+                data.as_ptr() as *const std::ffi::c_void,
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    pub fn get_string(&self, field_code: &str) -> Result<String, GdError> {
+        debug_call!(self, "get_string", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let dirfile = self.dirfile.as_ptr();
+        //first ask for the length (including the terminating NUL), then read
+        let len =
+            unsafe { ffi::gd_get_string(dirfile, field_code.as_ptr(), 0, std::ptr::null_mut()) };
+        if len == 0 {
+            return Err(self.last_error().unwrap());
+        }
+        let mut buffer: Vec<u8> = vec![0; len];
+        let len = unsafe {
+            ffi::gd_get_string(
+                dirfile,
+                field_code.as_ptr(),
+                buffer.len(),
+                buffer.as_mut_ptr() as *mut c_char,
+            )
+        };
+        if len == 0 {
+            return Err(self.last_error().unwrap());
+        }
+        let value = CStr::from_bytes_until_nul(&buffer).unwrap();
+        Ok(value.to_string_lossy().into_owned())
+    }
+
+    pub fn put_string(&mut self, field_code: &str, value: &str) -> Result<(), GdError> {
+        debug_call!(self, "put_string", field_code);
+        let field_code = CString::new(field_code).unwrap();
+        let value = CString::new(value).unwrap();
+        let ret_val = unsafe {
+            ffi::gd_put_string(self.dirfile.as_ptr(), field_code.as_ptr(), value.as_ptr())
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    pub fn get_sarray(&self, field_code: &str) -> Result<Vec<String>, GdError> {
+        let len = self.array_len(field_code)?;
+        self.get_sarray_slice(field_code, 0, len)
+    }
+
+    /// `n` elements of a SARRAY starting from element `start`
+    pub fn get_sarray_slice(
+        &self,
+        field_code: &str,
+        start: usize,
+        n: usize,
+    ) -> Result<Vec<String>, GdError> {
+        debug_call!(self, "get_sarray_slice", field_code, start, n);
+        let field_code = CString::new(field_code).unwrap();
+        let mut data: Vec<*const c_char> = vec![std::ptr::null(); n];
+        let ret_val = unsafe {
+            ffi::gd_get_sarray_slice(
+                self.dirfile.as_ptr(),
+                field_code.as_ptr(),
+                start as _,
+                n,
+                data.as_mut_ptr(),
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        //the strings belong to the library
+        Ok(data
+            .into_iter()
+            .map(|value| {
+                unsafe { CStr::from_ptr(value) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect())
+    }
+
+    /// overwrites the elements of a SARRAY starting from the first one
+    pub fn put_sarray(&mut self, field_code: &str, data: &[&str]) -> Result<(), GdError> {
+        self.put_sarray_slice(field_code, 0, data)
+    }
+
+    /// overwrites the elements of a SARRAY starting from element `start`
+    pub fn put_sarray_slice(
+        &mut self,
+        field_code: &str,
+        start: usize,
+        data: &[&str],
+    ) -> Result<(), GdError> {
+        debug_call!(self, "put_sarray_slice", field_code, start);
+        let field_code = CString::new(field_code).unwrap();
+        let data: Vec<CString> = data
+            .iter()
+            .map(|value| CString::new(*value).unwrap())
+            .collect();
+        let mut data_c: Vec<*const c_char> = data.iter().map(|value| value.as_ptr()).collect();
+        let ret_val = unsafe {
+            ffi::gd_put_sarray_slice(
+                self.dirfile.as_ptr(),
+                field_code.as_ptr(),
+                start as _,
+                data_c.len(),
+                data_c.as_mut_ptr(),
+            )
+        };
+        if ret_val != 0 {
+            return Err(self.last_error().unwrap());
+        }
+        Ok(())
+    }
+
+    /// field code and value of every CONST field, hidden ones and metafields included
+    pub fn constants<T: GdNative>(&self) -> Result<Vec<(String, T)>, GdError> {
+        debug_call!(self, "constants", data_type = T::GD_TYPE);
+        self.all_field_codes(EntryKind::Const)?
+            .into_iter()
+            .map(|field_code| {
+                let value = self.get_constant(&field_code)?;
+                Ok((field_code, value))
+            })
+            .collect()
+    }
+
+    /// field code and value of every STRING field, hidden ones and metafields included
+    pub fn strings(&self) -> Result<Vec<(String, String)>, GdError> {
+        debug_call!(self, "strings");
+        self.all_field_codes(EntryKind::String)?
+            .into_iter()
+            .map(|field_code| {
+                let value = self.get_string(&field_code)?;
+                Ok((field_code, value))
+            })
+            .collect()
+    }
+
+    /// full field codes of every field of type `kind`
+    fn all_field_codes(&self, kind: EntryKind) -> Result<Vec<String>, GdError> {
+        let filter = EntryFilter::new()
+            .kind(kind)
+            .include_hidden(true)
+            .include_aliases(false);
+        let mut field_codes: Vec<String> = self.field_codes(&filter)?.collect();
+        //metafields are only listed under their parent
+        let parents = EntryFilter::new()
+            .include_hidden(true)
+            .include_aliases(false);
+        for parent in self.field_codes(&parents)? {
+            let metafields = self.metafields(&parent, &filter)?;
+            field_codes.extend(metafields.map(|name| format!("{}/{}", parent, name)));
+        }
+        Ok(field_codes)
+    }
+}
@@ -0,0 +1,91 @@
+use crate::ffi;
+use crate::{Entry, EntryFilter, GdError, OpenOptions};
+use std::ffi::CString;
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// GetData does not quote the offending line when a spec line fails to parse, add it
+fn with_line(error: GdError, line: &str) -> GdError {
+    match error {
+        GdError::Format(msg) => GdError::Format(format!("{} in spec line \"{}\"", msg, line)),
+        error => error,
+    }
+}
+
+/// Adding entries from format file lines, eg. `"temp LINCOM raw 0.5 -20"`
+impl crate::Dirfile {
+    /// parses a format file line and adds the entry it defines to a fragment
+    pub fn add_spec(&mut self, line: &str, fragment_index: usize) -> Result<(), GdError> {
+        debug_call!(self, "add_spec", line, fragment_index);
+        let line_c = CString::new(line).unwrap();
+        let ret_val = unsafe {
+            ffi::gd_add_spec(
+                self.dirfile.as_ptr(),
+                line_c.as_ptr(),
+                fragment_index as c_int,
+            )
+        };
+        if ret_val != 0 {
+            return Err(with_line(self.last_error().unwrap(), line));
+        }
+        Ok(())
+    }
+
+    /// like `add_spec`, the line defines a metafield of `parent` (and names it without
+    /// the parent, eg. `"units STRING K"`)
+    pub fn madd_spec(&mut self, line: &str, parent: &str) -> Result<(), GdError> {
+        debug_call!(self, "madd_spec", line, parent);
+        let line_c = CString::new(line).unwrap();
+        let parent = CString::new(parent).unwrap();
+        let ret_val =
+            unsafe { ffi::gd_madd_spec(self.dirfile.as_ptr(), line_c.as_ptr(), parent.as_ptr()) };
+        if ret_val != 0 {
+            return Err(with_line(self.last_error().unwrap(), line));
+        }
+        Ok(())
+    }
+}
+
+static SCRATCH_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+impl Entry {
+    /// parses a format file line into an `Entry`, eg. to check a definition before adding
+    /// it to a dirfile
+    ///
+    /// GetData can only parse lines into a dirfile, so this goes through a scratch dirfile
+    /// in the temporary directory which is removed again afterwards. Input fields and
+    /// scalar parameters are not checked, they don't have to exist.
+    pub fn from_spec(line: &str) -> Result<Entry, GdError> {
+        let path = std::env::temp_dir().join(format!(
+            "rust_getdata_spec_{}_{}",
+            std::process::id(),
+            SCRATCH_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let path_str = path.to_str().ok_or_else(|| {
+            GdError::Argument(format!(
+                "Temporary directory is not valid UTF-8: {}",
+                path.display()
+            ))
+        })?;
+        let mut dirfile = OpenOptions::new()
+            .read_write(true)
+            .create_new(true)
+            .open(path_str)?;
+        //a failure is returned to the caller, there is nothing to report about the scratch
+        #[cfg(any(feature = "log", feature = "tracing"))]
+        dirfile.quiet();
+        let entry = dirfile.add_spec(line, 0).and_then(|()| {
+            //the scratch dirfile only holds INDEX and the new entry
+            let field_code = dirfile
+                .field_codes(&EntryFilter::new().include_hidden(true))?
+                .find(|field_code| field_code != "INDEX")
+                .ok_or_else(|| {
+                    GdError::Format(format!("No entry defined in spec line \"{}\"", line))
+                })?;
+            dirfile.get_entry(&field_code)
+        });
+        let _ = dirfile.discard();
+        let _ = std::fs::remove_dir_all(&path);
+        entry
+    }
+}
@@ -78,8 +78,8 @@ fn test_highlevel_open_close() {
     if path.exists() {
         std::fs::remove_dir_all(file_name).unwrap();
     }
-    let mut dirfile = super::Dirfile::open(file_name).unwrap();
-    dirfile.close();
+    let dirfile = super::Dirfile::open(file_name).unwrap();
+    dirfile.close().unwrap();
     //check for the existance of the folder
     let path = std::path::Path::new(file_name);
     assert!(path.exists());
@@ -106,7 +106,7 @@ fn test_highlevel_add_entry(){
     let lincom = Entry::new_lincom("test_lincom", vec!["testfield"], vec![1.0], vec![0.0]);
     dirfile.add(&lincom).unwrap();
 
-    dirfile.close();
+    dirfile.close().unwrap();
 
     // panic!("test_highlevel_add_entry");
     // //check for the existance of the folder
@@ -131,7 +131,7 @@ fn test_highlevel_add_entry(){
     assert!(format_file.contains("testfield_interp LINTERP testfield test_lut.lut"));
     assert!(format_file.contains("test_lincom LINCOM 1 testfield 1 0"));
 
-    let mut dirfile = Dirfile::open(file_name).unwrap();
+    let dirfile = Dirfile::open(file_name).unwrap();
     let entry = dirfile.get_entry("testfield").unwrap();
     assert_eq!(entry.get_field_code(), "testfield");
 
@@ -142,16 +142,14 @@ fn test_highlevel_add_entry(){
     dirfile.putdata(FieldOrEntry::Field("testfield".to_string()), &data).unwrap();
     dirfile.putdata(FieldOrEntry::Field("testfield".to_string()), &data).unwrap();
 
-    dirfile.close();
+    dirfile.close().unwrap();
 
     //open again to read and double check that it works
-    let mut dirfile = Dirfile::open(file_name).unwrap();
-    let mut data_read: Vec<f32> = vec![0.0; npoint];
-    let fc = CString::new("testfield").unwrap();
-    let n_pts = unsafe {ffi::gd_getdata(dirfile.dirfile.unwrap().as_ptr(), fc.as_ptr(), 0, 0, 3, 3, gd_type_t_GD_FLOAT32, data_read.as_mut_ptr() as *mut c_void) };
-    assert!(n_pts == npoint);
+    let dirfile = Dirfile::open(file_name).unwrap();
+    let data_read: Vec<f32> = dirfile.getdata(FieldOrEntry::Field("testfield".to_string()), 0, 0, 3, 3).unwrap();
+    assert_eq!(data_read.len(), npoint);
     assert_eq!(data, data_read);
-    dirfile.close();
+    dirfile.close().unwrap();
 
 
 
@@ -159,6 +157,1264 @@ fn test_highlevel_add_entry(){
     std::fs::remove_dir_all(file_name).unwrap();
 }
 
+#[test]
+fn test_highlevel_getdata(){
+    use super::*;
+    let file_name = "__testdirfile5__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("testfield", 4, GdTypes::Float32)).unwrap();
+    dirfile.add(&Entry::new_lincom("test_lincom", vec!["testfield"], vec![2.0], vec![1.0])).unwrap();
+    let data: Vec<f32> = (0..20).map(|x| x as f32).collect();
+    dirfile.putdata(FieldOrEntry::Field("testfield".to_string()), &data).unwrap();
+    dirfile.flush().unwrap();
+
+    //read back raw data in a different type than it was written
+    let raw: Vec<f64> = dirfile.getdata(FieldOrEntry::Field("testfield".to_string()), 1, 2, 2, 0).unwrap();
+    assert_eq!(raw, (6..14).map(|x| x as f64).collect::<Vec<f64>>());
+
+    //derived fields go through the same call
+    let lincom: Vec<f64> = dirfile.getdata(FieldOrEntry::Field("test_lincom".to_string()), 0, 0, 1, 0).unwrap();
+    assert_eq!(lincom, vec![1.0, 3.0, 5.0, 7.0]);
+
+    //reading past the end of the field gives a short read
+    let short: Vec<i32> = dirfile.getdata(FieldOrEntry::Field("testfield".to_string()), 4, 0, 2, 0).unwrap();
+    assert_eq!(short, vec![16, 17, 18, 19]);
+
+    //reading a field that does not exist is an error
+    let missing = dirfile.getdata::<f64>(FieldOrEntry::Field("nofield".to_string()), 0, 0, 1, 0);
+    assert!(matches!(missing, Err(GdError::BadCode(_))));
+
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_gd_types(){
+    use super::*;
+    let all_types = [
+        GdTypes::Null, GdTypes::Uint8, GdTypes::Int8, GdTypes::Uint16, GdTypes::Int16,
+        GdTypes::Uint32, GdTypes::Int32, GdTypes::Uint64, GdTypes::Int64, GdTypes::Float32,
+        GdTypes::Float64, GdTypes::Complex64, GdTypes::Complex128, GdTypes::String,
+    ];
+    for gd_type in all_types {
+        let gd_type_c: gd_type_t = gd_type.into();
+        assert_eq!(GdTypes::try_from(gd_type_c).unwrap(), gd_type);
+    }
+    assert!(matches!(GdTypes::try_from(0xdead as gd_type_t), Err(GdError::BadType(_))));
+    assert_eq!(<u8 as GdNative>::GD_TYPE, GdTypes::Uint8);
+    assert_eq!(<[f64; 2] as GdNative>::GD_TYPE, GdTypes::Complex128);
+
+    //putdata converts between the rust type and the type on disk
+    let file_name = "__testdirfile6__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("testfield", 1, GdTypes::Int16)).unwrap();
+    let data: Vec<f64> = vec![1.0, -2.0, 3.0];
+    assert_eq!(dirfile.putdata(FieldOrEntry::Field("testfield".to_string()), &data).unwrap(), 3);
+    dirfile.flush().unwrap();
+    let data_read: Vec<i16> = dirfile.getdata(FieldOrEntry::Field("testfield".to_string()), 0, 0, 0, 3).unwrap();
+    assert_eq!(data_read, vec![1, -2, 3]);
+    let data_read: Vec<[f32; 2]> = dirfile.getdata(FieldOrEntry::Field("testfield".to_string()), 0, 0, 0, 3).unwrap();
+    assert_eq!(data_read, vec![[1.0, 0.0], [-2.0, 0.0], [3.0, 0.0]]);
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_seek_putdata_at(){
+    use super::*;
+    let file_name = "__testdirfile7__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let field = || FieldOrEntry::Field("testfield".to_string());
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("testfield", 2, GdTypes::Int32)).unwrap();
+
+    //sequential writes advance the I/O pointer
+    dirfile.putdata(field(), &[1, 2, 3, 4]).unwrap();
+    assert_eq!(dirfile.tell("testfield").unwrap(), 4);
+
+    //leave a gap, then backfill it
+    dirfile.putdata_at(field(), 3, 0, &[7, 8]).unwrap();
+    assert_eq!(dirfile.tell("testfield").unwrap(), 8);
+    dirfile.putdata_at(field(), 2, 0, &[5, 6]).unwrap();
+    //rewrite a bad sample in the middle of a frame
+    dirfile.putdata_at(field(), 0, 1, &[20]).unwrap();
+
+    //go back to appending
+    assert_eq!(dirfile.seek("testfield", 0, 0, Whence::End).unwrap(), 8);
+    dirfile.putdata(field(), &[9, 10]).unwrap();
+    dirfile.flush().unwrap();
+
+    let data: Vec<i32> = dirfile.getdata(field(), 0, 0, 5, 0).unwrap();
+    assert_eq!(data, vec![1, 20, 3, 4, 5, 6, 7, 8, 9, 10]);
+
+    //relative seeks
+    assert_eq!(dirfile.seek("testfield", 1, 0, Whence::Set).unwrap(), 2);
+    assert_eq!(dirfile.seek("testfield", 0, 3, Whence::Cur).unwrap(), 5);
+
+    //seek_write pads the field when seeking past the end
+    assert_eq!(dirfile.seek_write("testfield", 10, 0, Whence::Set).unwrap(), 20);
+    dirfile.putdata(field(), &[11, 12]).unwrap();
+    dirfile.flush().unwrap();
+    let data: Vec<i32> = dirfile.getdata(field(), 9, 0, 2, 0).unwrap();
+    assert_eq!(data, vec![0, 0, 11, 12]);
+
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_putdata_derived(){
+    use super::*;
+    let file_name = "__testdirfile8__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("counts", 1, GdTypes::Int32)).unwrap();
+    dirfile.add(&Entry::new_lincom("volts", vec!["counts"], vec![0.5], vec![-1.0])).unwrap();
+    dirfile.add(&Entry::new_lincom("sum", vec!["counts", "volts"], vec![1.0, 1.0], vec![0.0, 0.0])).unwrap();
+
+    //engineering units in, raw counts stored
+    let volts: Vec<f64> = vec![0.0, 1.0, 2.0];
+    assert_eq!(dirfile.putdata(FieldOrEntry::Field("volts".to_string()), &volts).unwrap(), 3);
+    dirfile.flush().unwrap();
+    let counts: Vec<i32> = dirfile.getdata(FieldOrEntry::Field("counts".to_string()), 0, 0, 0, 3).unwrap();
+    assert_eq!(counts, vec![2, 4, 6]);
+
+    //a LINCOM of two fields can not be inverted, GetData reports it
+    assert!(dirfile.putdata(FieldOrEntry::Field("sum".to_string()), &volts).is_err());
+    //INDEX is never writable
+    let error = dirfile.putdata(FieldOrEntry::Field("INDEX".to_string()), &volts).unwrap_err();
+    assert!(matches!(error, GdError::BadFieldType(_)));
+
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_get_entry_all_types(){
+    use super::*;
+    let file_name = "__testdirfile9__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    std::fs::create_dir(file_name).unwrap();
+    std::fs::write(format!("{}/format", file_name), "/VERSION 10
+raw1 RAW UINT16 8
+lin LINCOM 2 raw1 2 3 raw1 4 5
+lt LINTERP raw1 table.lut
+bit1 BIT raw1 3 2
+sbit1 SBIT raw1 1 4
+mul MULTIPLY raw1 lin
+div DIVIDE raw1 lin
+ph PHASE raw1 -3
+poly POLYNOM raw1 1 2 3
+rec RECIP raw1 5
+win WINDOW raw1 bit1 GT 1.5
+mplex MPLEX raw1 bit1 2 4
+c CONST FLOAT64 3.5
+ca CARRAY INT32 1 2 3
+s STRING hello
+sa SARRAY a b c
+ind INDIR raw1 ca
+sind SINDIR raw1 sa
+").unwrap();
+
+    let cs = |s: &str| CString::new(s).unwrap();
+    let dirfile = Dirfile::open(file_name).unwrap();
+    let get = |field_code: &str| {
+        let entry = dirfile.get_entry(field_code).unwrap();
+        assert_eq!(entry.get_field_code(), field_code);
+        entry.field_type
+    };
+    assert_eq!(get("raw1"), EntryType::Raw(RawData { spf: 8.into(), gd_type: GdTypes::Uint16 }));
+    assert_eq!(get("lin"), EntryType::Lincom(LincomData {
+        in_fields: vec![cs("raw1"), cs("raw1")],
+        m: vec![2.0.into(), 4.0.into()],
+        b: vec![3.0.into(), 5.0.into()],
+    }));
+    assert_eq!(get("lt"), EntryType::Linterp(LinterpData { in_field: cs("raw1"), table: cs("table.lut") }));
+    assert_eq!(get("bit1"), EntryType::Bit(BitData { in_field: cs("raw1"), bitnum: 3.into(), numbits: 2.into() }));
+    assert_eq!(get("sbit1"), EntryType::Sbit(BitData { in_field: cs("raw1"), bitnum: 1.into(), numbits: 4.into() }));
+    assert_eq!(get("mul"), EntryType::Multiply(BinaryData { in_fields: (cs("raw1"), cs("lin")) }));
+    assert_eq!(get("div"), EntryType::Divide(BinaryData { in_fields: (cs("raw1"), cs("lin")) }));
+    assert_eq!(get("ph"), EntryType::Phase(PhaseData { in_field: cs("raw1"), shift: (-3).into() }));
+    assert_eq!(get("poly"), EntryType::Polynom(PolynomData { in_field: cs("raw1"), a: vec![1.0.into(), 2.0.into(), 3.0.into()] }));
+    assert_eq!(get("rec"), EntryType::Recip(RecipData { in_field: cs("raw1"), dividend: 5.0.into() }));
+    assert_eq!(get("win"), EntryType::Window(WindowData {
+        in_fields: (cs("raw1"), cs("bit1")),
+        op: WindowOp::Gt(1.5.into()),
+    }));
+    assert_eq!(get("mplex"), EntryType::Mplex(MplexData {
+        in_fields: (cs("raw1"), cs("bit1")),
+        count_val: 2.into(),
+        period: 4.into(),
+    }));
+    assert_eq!(get("c"), EntryType::Const(ConstData { gd_type: GdTypes::Float64 }));
+    assert_eq!(get("ca"), EntryType::Carray(CarrayData { gd_type: GdTypes::Int32, array_len: 3 }));
+    assert_eq!(get("s"), EntryType::String);
+    assert_eq!(get("sa"), EntryType::Sarray(SarrayData { array_len: 3 }));
+    assert_eq!(get("ind"), EntryType::Indir(BinaryData { in_fields: (cs("raw1"), cs("ca")) }));
+    assert_eq!(get("sind"), EntryType::Sindir(BinaryData { in_fields: (cs("raw1"), cs("sa")) }));
+    assert_eq!(get("INDEX"), EntryType::Index);
+
+    //decoded entries can be added straight back
+    let mut dirfile2 = Dirfile::open(&format!("{}/copy", file_name)).unwrap();
+    dirfile2.add(&dirfile.get_entry("raw1").unwrap()).unwrap();
+    dirfile2.add(&dirfile.get_entry("poly").unwrap()).unwrap();
+    assert_eq!(dirfile2.get_entry("poly").unwrap().field_type, get("poly"));
+    dirfile2.close().unwrap();
+
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_new_entries(){
+    use super::*;
+    let file_name = "__testdirfile10__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    let entries = vec![
+        Entry::new_raw("status", 1, GdTypes::Uint16),
+        Entry::new_raw("temp_raw", 1, GdTypes::Int32),
+        Entry::new_bit("heater_on", "status", 0, 1),
+        Entry::new_sbit("mode", "status", 2, 3),
+        Entry::new_polynom("temp", "temp_raw", vec![-273.15, 0.01, 1e-6]),
+        Entry::new_multiply("temp_sq", "temp", "temp"),
+        Entry::new_divide("temp_ratio", "temp", "temp_sq"),
+        Entry::new_phase("temp_late", "temp", 4),
+        Entry::new_recip("temp_inv", "temp", 2.0),
+        Entry::new_window("temp_good", "temp", "status", WindowOp::Clr(0x8.into())),
+        Entry::new_window("temp_high", "temp", "temp", WindowOp::Gt(30.0.into())),
+        Entry::new_mplex("temp_mplex", "temp_raw", "mode", 1, 4),
+        Entry::new_const("gain", GdTypes::Float64),
+        Entry::new_carray("cal", GdTypes::Float32, 4),
+        Entry::new_string("note"),
+        Entry::new_sarray("labels", 3),
+        Entry::new_indir("cal_indexed", "mode", "cal"),
+        Entry::new_sindir("label_indexed", "mode", "labels"),
+    ];
+    for entry in &entries {
+        dirfile.add(entry).unwrap();
+    }
+    dirfile.metaflush().unwrap();
+    dirfile.close().unwrap();
+
+    let dirfile = Dirfile::open(file_name).unwrap();
+    for entry in &entries {
+        let entry_read = dirfile.get_entry(entry.get_field_code()).unwrap();
+        assert_eq!(entry_read.field_type, entry.field_type);
+    }
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_scalar_references(){
+    use super::*;
+    let file_name = "__testdirfile11__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_const("gain", GdTypes::Float64)).unwrap();
+    dirfile.add(&Entry::new_const("samples", GdTypes::Uint32)).unwrap();
+    dirfile.add(&Entry::new_carray("cal", GdTypes::Float64, 3)).unwrap();
+    dirfile.add(&Entry::new_raw("counts", Scalar::reference("samples"), GdTypes::Int32)).unwrap();
+    dirfile.add(&Entry::new_lincom(
+        "volts",
+        vec!["counts"],
+        vec![Scalar::reference("gain")],
+        vec![Scalar::element("cal", 1)],
+    )).unwrap();
+    dirfile.add(&Entry::new_polynom(
+        "temp",
+        "counts",
+        vec![Scalar::Literal(1.0), Scalar::element("cal", 0), Scalar::element("cal", 2)],
+    )).unwrap();
+    dirfile.add(&Entry::new_bit("flag", "counts", Scalar::reference("samples"), 1)).unwrap();
+    dirfile.metaflush().unwrap();
+    dirfile.close().unwrap();
+
+    let format_file = std::fs::read_to_string(format!("{}/format", file_name)).unwrap();
+    assert!(format_file.contains("volts LINCOM 1 counts gain cal<1>"));
+
+    let dirfile = Dirfile::open(file_name).unwrap();
+    let cs = |s: &str| CString::new(s).unwrap();
+    match dirfile.get_entry("counts").unwrap().field_type {
+        EntryType::Raw(raw_data) => assert_eq!(raw_data.spf, Scalar::reference("samples")),
+        _ => panic!("counts should be RAW"),
+    }
+    assert_eq!(dirfile.get_entry("volts").unwrap().field_type, EntryType::Lincom(LincomData {
+        in_fields: vec![cs("counts")],
+        m: vec![Scalar::Reference { field_code: cs("gain"), index: None }],
+        b: vec![Scalar::Reference { field_code: cs("cal"), index: Some(1) }],
+    }));
+    assert_eq!(dirfile.get_entry("temp").unwrap().field_type, EntryType::Polynom(PolynomData {
+        in_field: cs("counts"),
+        a: vec![Scalar::Literal(1.0), Scalar::element("cal", 0), Scalar::element("cal", 2)],
+    }));
+    assert_eq!(dirfile.get_entry("flag").unwrap().field_type, EntryType::Bit(BitData {
+        in_field: cs("counts"),
+        bitnum: Scalar::reference("samples"),
+        numbits: Scalar::Literal(1),
+    }));
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_entry_list(){
+    use super::*;
+    let file_name = "__testdirfile12__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("a_raw", 1, GdTypes::Int32)).unwrap();
+    dirfile.add(&Entry::new_raw("b_raw", 2, GdTypes::Float64)).unwrap();
+    dirfile.add(&Entry::new_lincom("c_lincom", vec!["a_raw"], vec![1.0], vec![0.0])).unwrap();
+    dirfile.add(&Entry::new_const("d_const", GdTypes::Float64)).unwrap();
+    dirfile.add(&Entry::new_string("e_string")).unwrap();
+    dirfile.add_alias("f_alias", FieldOrEntry::Field("a_raw".to_string())).unwrap();
+
+    let sorted = |filter: EntryFilter| {
+        let mut field_codes: Vec<String> = dirfile.field_codes(&filter).unwrap().collect();
+        field_codes.sort();
+        field_codes
+    };
+    assert_eq!(sorted(EntryFilter::new()), vec!["INDEX", "a_raw", "b_raw", "c_lincom", "d_const", "e_string", "f_alias"]);
+    assert_eq!(sorted(EntryFilter::new().include_aliases(false)), vec!["INDEX", "a_raw", "b_raw", "c_lincom", "d_const", "e_string"]);
+    assert_eq!(sorted(EntryFilter::new().kind(EntryKind::Raw)), vec!["a_raw", "b_raw"]);
+    assert_eq!(sorted(EntryFilter::new().scalars()), vec!["d_const", "e_string"]);
+    assert_eq!(sorted(EntryFilter::new().vectors().include_aliases(false)), vec!["INDEX", "a_raw", "b_raw", "c_lincom"]);
+    assert_eq!(dirfile.nentries(&EntryFilter::new().kind(EntryKind::Raw)).unwrap(), 2);
+
+    let entries: Vec<Entry> = dirfile
+        .entries(&EntryFilter::new().kind(EntryKind::Raw))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().all(|entry| entry.field_type.kind() == EntryKind::Raw));
+
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_scalar_io(){
+    use super::*;
+    let file_name = "__testdirfile13__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_const("gain", GdTypes::Float64)).unwrap();
+    dirfile.add(&Entry::new_const("run", GdTypes::Int32)).unwrap();
+    dirfile.add(&Entry::new_carray("cal", GdTypes::Float32, 4)).unwrap();
+    dirfile.add(&Entry::new_string("operator")).unwrap();
+    dirfile.add(&Entry::new_sarray("labels", 3)).unwrap();
+
+    dirfile.put_constant("gain", 2.5f64).unwrap();
+    dirfile.put_constant("run", 42u8).unwrap();
+    assert_eq!(dirfile.get_constant::<f64>("gain").unwrap(), 2.5);
+    assert_eq!(dirfile.get_constant::<i64>("run").unwrap(), 42);
+    assert_eq!(dirfile.get_constant::<f32>("run").unwrap(), 42.0);
+
+    assert_eq!(dirfile.array_len("cal").unwrap(), 4);
+    dirfile.put_carray("cal", &[1.0f64, 2.0, 3.0, 4.0]).unwrap();
+    dirfile.put_carray_slice("cal", 2, &[30i32]).unwrap();
+    assert_eq!(dirfile.get_carray::<f64>("cal").unwrap(), vec![1.0, 2.0, 30.0, 4.0]);
+    assert_eq!(dirfile.get_carray_slice::<i32>("cal", 1, 2).unwrap(), vec![2, 30]);
+    assert!(dirfile.get_carray_slice::<f64>("cal", 3, 2).is_err());
+
+    dirfile.put_string("operator", "night shift, cryostat at 4K").unwrap();
+    assert_eq!(dirfile.get_string("operator").unwrap(), "night shift, cryostat at 4K");
+
+    assert_eq!(dirfile.array_len("labels").unwrap(), 3);
+    dirfile.put_sarray("labels", &["x", "y", "z"]).unwrap();
+    dirfile.put_sarray_slice("labels", 1, &["why"]).unwrap();
+    assert_eq!(dirfile.get_sarray("labels").unwrap(), vec!["x", "why", "z"]);
+    assert_eq!(dirfile.get_sarray_slice("labels", 2, 1).unwrap(), vec!["z"]);
+
+    //hidden fields and metafields are listed too
+    dirfile.add(&Entry::new_const("offset", GdTypes::Float64)).unwrap();
+    dirfile.put_constant("offset", -1.0f64).unwrap();
+    dirfile.hide("offset").unwrap();
+    dirfile.madd(&Entry::new_const("scale", GdTypes::Float64), "cal").unwrap();
+    dirfile.put_constant("cal/scale", 0.5f64).unwrap();
+    dirfile.madd(&Entry::new_string("units"), "cal").unwrap();
+    dirfile.put_string("cal/units", "K").unwrap();
+
+    let mut constants: Vec<(String, f64)> = dirfile.constants().unwrap();
+    constants.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(constants, vec![
+        ("cal/scale".to_string(), 0.5),
+        ("gain".to_string(), 2.5),
+        ("offset".to_string(), -1.0),
+        ("run".to_string(), 42.0),
+    ]);
+    let mut strings = dirfile.strings().unwrap();
+    strings.sort();
+    assert_eq!(strings, vec![
+        ("cal/units".to_string(), "K".to_string()),
+        ("operator".to_string(), "night shift, cryostat at 4K".to_string()),
+    ]);
+
+    //values survive a reopen
+    dirfile.close().unwrap();
+    let dirfile = Dirfile::open(file_name).unwrap();
+    assert_eq!(dirfile.get_constant::<f64>("gain").unwrap(), 2.5);
+    assert_eq!(dirfile.get_sarray("labels").unwrap(), vec!["x", "why", "z"]);
+    assert!(dirfile.get_string("gain").is_err());
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_geometry(){
+    use super::*;
+    let file_name = "__testdirfile14__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("fast", 4, GdTypes::Int16)).unwrap();
+    dirfile.add(&Entry::new_raw("slow", 1, GdTypes::Float64)).unwrap();
+    dirfile.add(&Entry::new_lincom("fast_cal", vec!["fast"], vec![0.5], vec![0.0])).unwrap();
+    dirfile.add(&Entry::new_phase("slow_late", "slow", 2)).unwrap();
+    dirfile.add(&Entry::new_const("gain", GdTypes::Float32)).unwrap();
+    dirfile.putdata(FieldOrEntry::Field("fast".to_string()), &[0i16; 10]).unwrap();
+    dirfile.putdata(FieldOrEntry::Field("slow".to_string()), &[0.0f64; 3]).unwrap();
+    dirfile.flush().unwrap();
+
+    //"fast" was added first so it is the reference field
+    assert_eq!(dirfile.nframes().unwrap(), 2);
+    assert_eq!(dirfile.spf("fast").unwrap(), 4);
+    assert_eq!(dirfile.spf("fast_cal").unwrap(), 4);
+    assert_eq!(dirfile.eof("fast").unwrap(), 10);
+    assert_eq!(dirfile.eof("slow").unwrap(), 3);
+    assert_eq!(dirfile.bof("slow").unwrap(), 0);
+    assert!(dirfile.spf("gain").is_err());
+
+    assert_eq!(dirfile.native_type("fast").unwrap(), GdTypes::Int16);
+    assert_eq!(dirfile.native_type("fast_cal").unwrap(), GdTypes::Float64);
+    assert_eq!(dirfile.native_type("gain").unwrap(), GdTypes::Float32);
+
+    let raw_filename = dirfile.raw_filename("fast").unwrap();
+    assert!(raw_filename.ends_with("fast"));
+    assert!(raw_filename.exists());
+    assert!(dirfile.raw_filename("fast_cal").is_err());
+    assert!(matches!(dirfile.eof("nofield"), Err(GdError::BadCode(_))));
+
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_open_options(){
+    use super::*;
+    let file_name = "__testdirfile15__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    //read-only opening of a missing dirfile fails and leaves nothing behind
+    assert!(OpenOptions::new().open(file_name).is_err());
+    assert!(!path.exists());
+
+    let mut dirfile = OpenOptions::new()
+        .read_write(true)
+        .create_new(true)
+        .endianness(Endianness::Big)
+        .encoding(Encoding::None)
+        .open(file_name)
+        .unwrap();
+    dirfile.add(&Entry::new_raw("data", 1, GdTypes::Int32)).unwrap();
+    dirfile.putdata(FieldOrEntry::Field("data".to_string()), &[1i32, 2, 3]).unwrap();
+    dirfile.close().unwrap();
+
+    //the dirfile exists now
+    assert!(OpenOptions::new().read_write(true).create_new(true).open(file_name).is_err());
+
+    let dirfile = OpenOptions::new().open(file_name).unwrap();
+    let data: Vec<i32> = dirfile.getdata(FieldOrEntry::Field("data".to_string()), 0, 0, 3, 0).unwrap();
+    assert_eq!(data, vec![1, 2, 3]);
+    assert!(matches!(
+        dirfile.putdata(FieldOrEntry::Field("data".to_string()), &[4i32]),
+        Err(GdError::Accmode(_))
+    ));
+    dirfile.close().unwrap();
+
+    let dirfile = OpenOptions::new().read_write(true).truncate(true).open(file_name).unwrap();
+    assert!(dirfile.get_entry("data").is_err());
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_close_discard(){
+    use super::*;
+    let file_name = "__testdirfile16__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    //dropping the dirfile writes the metadata like close does
+    {
+        let mut dirfile = Dirfile::open(file_name).unwrap();
+        dirfile.add(&Entry::new_raw("kept", 1, GdTypes::Float64)).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    assert!(dirfile.get_entry("kept").is_ok());
+
+    //discarding drops whatever was not flushed yet
+    dirfile.add(&Entry::new_raw("flushed", 1, GdTypes::Float64)).unwrap();
+    dirfile.metaflush().unwrap();
+    dirfile.add(&Entry::new_raw("lost", 1, GdTypes::Float64)).unwrap();
+    dirfile.discard().unwrap();
+
+    let dirfile = Dirfile::open(file_name).unwrap();
+    assert!(dirfile.get_entry("flushed").is_ok());
+    assert!(matches!(dirfile.get_entry("lost"), Err(GdError::BadCode(_))));
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_parser_callback(){
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    let file_name = "__testdirfile17__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    std::fs::create_dir(file_name).unwrap();
+    std::fs::write(format!("{}/format", file_name), "/VERSION 10
+good RAW FLOAT64 1
+typo RAW FLAOT64 1
+junk line here
+").unwrap();
+
+    //without a callback the typo is fatal
+    assert!(matches!(OpenOptions::new().open(file_name), Err(GdError::Format(_))));
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let seen_cb = seen.clone();
+    let dirfile = OpenOptions::new()
+        .open_with_callback(file_name, move |error: &SyntaxError| {
+            seen_cb.borrow_mut().push((error.line_num, error.kind, error.line.to_string()));
+            assert!(error.fragment.ends_with("format"));
+            if error.line.starts_with("typo") {
+                SyntaxAction::Substitute("typo RAW FLOAT64 1".to_string())
+            } else {
+                SyntaxAction::Ignore
+            }
+        })
+        .unwrap();
+    assert!(dirfile.get_entry("good").is_ok());
+    assert_eq!(dirfile.native_type("typo").unwrap(), GdTypes::Float64);
+    assert!(dirfile.get_entry("junk").is_err());
+    let seen = seen.borrow();
+    assert_eq!(seen.len(), 2);
+    assert_eq!(seen[0].0, 3);
+    assert_eq!(seen[0].1, SyntaxErrorKind::BadType);
+    assert_eq!(seen[0].2, "typo RAW FLAOT64 1");
+    assert_eq!(seen[1].0, 4);
+    dirfile.close().unwrap();
+
+    //aborting, or panicking, fails the open
+    assert!(matches!(
+        OpenOptions::new().open_with_callback(file_name, |_: &SyntaxError| SyntaxAction::Abort),
+        Err(GdError::Format(_))
+    ));
+    assert!(matches!(
+        OpenOptions::new().open_with_callback(file_name, |_: &SyntaxError| -> SyntaxAction { panic!("bad format file") }),
+        Err(GdError::Format(_))
+    ));
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_fragments(){
+    use super::*;
+    let file_name = "__testdirfile18__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    assert_eq!(dirfile.nfragments().unwrap(), 1);
+    assert!(dirfile.fragment_name(0).unwrap().ends_with("format"));
+
+    let sub = dirfile.include("sub", 0, &OpenOptions::new().create(true)).unwrap();
+    let affixed = dirfile.include_affix("affixed", sub, Some("a_"), Some("_z"), &OpenOptions::new().create(true)).unwrap();
+    assert_eq!(dirfile.nfragments().unwrap(), 3);
+    assert!(dirfile.fragment_name(sub).unwrap().ends_with("sub"));
+    assert_eq!(dirfile.parent_fragment(sub).unwrap(), 0);
+    assert_eq!(dirfile.parent_fragment(affixed).unwrap(), sub);
+    assert!(dirfile.parent_fragment(0).is_err());
+    assert_eq!(dirfile.fragment_affixes(affixed).unwrap(), ("a_".to_string(), "_z".to_string()));
+    assert_eq!(dirfile.fragment_affixes(sub).unwrap(), (String::new(), String::new()));
+
+    //entries go to the fragment they name
+    let mut entry = Entry::new_raw("in_sub", 1, GdTypes::Float64);
+    entry.set_fragment_index(sub);
+    dirfile.add(&entry).unwrap();
+    let mut entry = Entry::new_const("a_gain_z", GdTypes::Float64);
+    entry.set_fragment_index(affixed);
+    dirfile.add(&entry).unwrap();
+    dirfile.add(&Entry::new_raw("in_top", 1, GdTypes::Float64)).unwrap();
+    assert_eq!(dirfile.fragment_index("in_sub").unwrap(), sub);
+    assert_eq!(dirfile.fragment_index("in_top").unwrap(), 0);
+    assert_eq!(dirfile.get_entry("in_sub").unwrap().fragment_index(), sub);
+
+    dirfile.alter_affixes(affixed, Some("b_"), None).unwrap();
+    assert!(dirfile.get_entry("b_gain_z").is_ok());
+    dirfile.rewrite_fragment(Fragment::All).unwrap();
+    dirfile.close().unwrap();
+
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    assert_eq!(dirfile.nfragments().unwrap(), 3);
+    assert!(dirfile.get_entry("b_gain_z").is_ok());
+    dirfile.uninclude(sub, true).unwrap();
+    assert_eq!(dirfile.nfragments().unwrap(), 1);
+    assert!(dirfile.get_entry("in_sub").is_err());
+    dirfile.close().unwrap();
+    assert!(!path.join("sub").exists());
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_encoding(){
+    use super::*;
+    let file_name = "__testdirfile19__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    assert_eq!(Encoding::None.support(), EncodingSupport::ReadWrite);
+    assert_eq!(Encoding::Text.support(), EncodingSupport::ReadWrite);
+
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("data", 1, GdTypes::Int32)).unwrap();
+    dirfile.putdata(FieldOrEntry::Field("data".to_string()), &[1i32, 2, 3, 4]).unwrap();
+    dirfile.flush().unwrap();
+    assert_eq!(dirfile.encoding(0).unwrap(), Encoding::None);
+
+    //recoding rewrites the data file
+    dirfile.alter_encoding(Encoding::Text, 0, true).unwrap();
+    assert_eq!(dirfile.encoding(0).unwrap(), Encoding::Text);
+    assert!(path.join("data.txt").exists());
+    assert!(!path.join("data").exists());
+    let data: Vec<i32> = dirfile.getdata(FieldOrEntry::Field("data".to_string()), 0, 0, 4, 0).unwrap();
+    assert_eq!(data, vec![1, 2, 3, 4]);
+
+    //compression depends on how libgetdata was built
+    match Encoding::Gzip.support() {
+        EncodingSupport::ReadWrite => {
+            dirfile.alter_encoding(Encoding::Gzip, Fragment::All, true).unwrap();
+            assert_eq!(dirfile.encoding(0).unwrap(), Encoding::Gzip);
+            let data: Vec<i32> = dirfile.getdata(FieldOrEntry::Field("data".to_string()), 0, 0, 4, 0).unwrap();
+            assert_eq!(data, vec![1, 2, 3, 4]);
+        }
+        _ => assert!(matches!(
+            dirfile.alter_encoding(Encoding::Gzip, Fragment::All, true),
+            Err(GdError::UnsupportedEncoding(Encoding::Gzip, _))
+        )),
+    }
+
+    //without recoding only the metadata changes
+    dirfile.alter_encoding(Encoding::None, 0, false).unwrap();
+    assert_eq!(dirfile.encoding(0).unwrap(), Encoding::None);
+
+    //the metadata can name an encoding this build can't handle, eg. for files
+    //compressed elsewhere
+    let known = [Encoding::Gzip, Encoding::Bzip2, Encoding::Lzma, Encoding::Slim, Encoding::Zzip,
+        Encoding::Zzslim, Encoding::Flac, Encoding::Sie];
+    if let Some(unbuilt) = known.into_iter().find(|encoding| encoding.support() == EncodingSupport::Unsupported) {
+        assert!(matches!(
+            dirfile.alter_encoding(unbuilt, 0, true),
+            Err(GdError::UnsupportedEncoding(encoding, _)) if encoding == unbuilt
+        ));
+        dirfile.alter_encoding(unbuilt, 0, false).unwrap();
+        assert_eq!(dirfile.encoding(0).unwrap(), unbuilt);
+        //reading it then says which encoding is missing
+        assert!(matches!(
+            dirfile.getdata::<i32>(FieldOrEntry::Field("data".to_string()), 0, 0, 1, 0),
+            Err(GdError::UnsupportedEncoding(encoding, _)) if encoding == unbuilt
+        ));
+        dirfile.alter_encoding(Encoding::None, 0, false).unwrap();
+    }
+
+    //an encoding GetData does not recognise is a value, not an error
+    std::fs::write(path.join("odd"), "/ENCODING nosuchscheme\n").unwrap();
+    let odd = dirfile.include("odd", 0, &OpenOptions::new()).unwrap();
+    assert_eq!(dirfile.encoding(odd).unwrap(), Encoding::Unknown);
+    assert_eq!(Encoding::Unknown.support(), EncodingSupport::Unsupported);
+    assert!(matches!(
+        dirfile.alter_encoding(Encoding::Unknown, 0, false),
+        Err(GdError::UnsupportedEncoding(Encoding::Unknown, _))
+    ));
+    assert_eq!(Encoding::from(0xdead_0000 as std::os::raw::c_ulong), Encoding::Unknown);
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_endianness_frame_offset(){
+    use super::*;
+    let file_name = "__testdirfile20__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = OpenOptions::new()
+        .read_write(true)
+        .create(true)
+        .endianness(Endianness::Little)
+        .open(file_name)
+        .unwrap();
+    dirfile.add(&Entry::new_raw("data", 1, GdTypes::Int16)).unwrap();
+    dirfile.putdata(FieldOrEntry::Field("data".to_string()), &[1i16, 2]).unwrap();
+    dirfile.flush().unwrap();
+    assert_eq!(dirfile.endianness(0).unwrap(), Endianness::Little);
+    assert!(!dirfile.arm_endian(0).unwrap());
+    assert_eq!(std::fs::read(path.join("data")).unwrap(), vec![1, 0, 2, 0]);
+
+    //recoding swaps the bytes on disk, the values stay the same
+    dirfile.alter_endianness(Endianness::Big, false, 0, true).unwrap();
+    assert_eq!(dirfile.endianness(0).unwrap(), Endianness::Big);
+    assert_eq!(std::fs::read(path.join("data")).unwrap(), vec![0, 1, 0, 2]);
+    let data: Vec<i16> = dirfile.getdata(FieldOrEntry::Field("data".to_string()), 0, 0, 2, 0).unwrap();
+    assert_eq!(data, vec![1, 2]);
+
+    //otherwise the same bytes are read the other way round
+    dirfile.alter_endianness(Endianness::Little, true, Fragment::All, false).unwrap();
+    assert!(dirfile.arm_endian(0).unwrap());
+    let data: Vec<i16> = dirfile.getdata(FieldOrEntry::Field("data".to_string()), 0, 0, 2, 0).unwrap();
+    assert_eq!(data, vec![256, 512]);
+
+    assert_eq!(dirfile.frame_offset(0).unwrap(), 0);
+    dirfile.alter_frame_offset(2, 0, false).unwrap();
+    assert_eq!(dirfile.frame_offset(0).unwrap(), 2);
+    assert_eq!(dirfile.bof("data").unwrap(), 2);
+    let data: Vec<i16> = dirfile.getdata(FieldOrEntry::Field("data".to_string()), 2, 0, 2, 0).unwrap();
+    assert_eq!(data, vec![256, 512]);
+    dirfile.close().unwrap();
+
+    let dirfile = Dirfile::open(file_name).unwrap();
+    assert_eq!(dirfile.frame_offset(0).unwrap(), 2);
+    assert_eq!(dirfile.endianness(0).unwrap(), Endianness::Little);
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_protection(){
+    use super::*;
+    let file_name = "__testdirfile21__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    let sub = dirfile.include("sub", 0, &OpenOptions::new().create(true)).unwrap();
+    dirfile.add(&Entry::new_raw("data", 1, GdTypes::Float64)).unwrap();
+    let mut entry = Entry::new_const("gain", GdTypes::Float64);
+    entry.set_fragment_index(sub);
+    dirfile.add(&entry).unwrap();
+    assert_eq!(dirfile.protection(0).unwrap(), Protection::None);
+
+    dirfile.alter_protection(Protection::Data, 0).unwrap();
+    assert_eq!(dirfile.protection(0).unwrap(), Protection::Data);
+    assert_eq!(dirfile.protection(sub).unwrap(), Protection::None);
+    assert!(matches!(
+        dirfile.putdata(FieldOrEntry::Field("data".to_string()), &[1.0f64]),
+        Err(GdError::Protected(_))
+    ));
+    //the metadata can still change
+    dirfile.add(&Entry::new_raw("more", 1, GdTypes::Float64)).unwrap();
+
+    dirfile.alter_protection(Protection::All, Fragment::All).unwrap();
+    assert_eq!(dirfile.protection(sub).unwrap(), Protection::All);
+    let mut entry = Entry::new_const("offset", GdTypes::Float64);
+    entry.set_fragment_index(sub);
+    assert!(matches!(dirfile.add(&entry), Err(GdError::Protected(_))));
+    dirfile.close().unwrap();
+
+    //the protection is stored in the format files
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    assert_eq!(dirfile.protection(0).unwrap(), Protection::All);
+    dirfile.alter_protection(Protection::None, Fragment::All).unwrap();
+    dirfile.putdata(FieldOrEntry::Field("data".to_string()), &[1.0f64]).unwrap();
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_entry_edit(){
+    use super::*;
+    let file_name = "__testdirfile22__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("data", 1, GdTypes::Int16)).unwrap();
+    dirfile.add(&Entry::new_lincom("cal", vec!["data"], vec![1.0], vec![0.0])).unwrap();
+    dirfile.add(&Entry::new_const("gain", GdTypes::Float64)).unwrap();
+    dirfile.put_constant("gain", 3.0f64).unwrap();
+    dirfile.add(&Entry::new_recip("inv", "data", Scalar::reference("gain"))).unwrap();
+    dirfile.putdata(FieldOrEntry::Field("data".to_string()), &[1i16, 2, 3]).unwrap();
+    dirfile.flush().unwrap();
+    let read = |dirfile: &Dirfile, field_code: &str| -> Vec<f64> {
+        dirfile.getdata(FieldOrEntry::Field(field_code.to_string()), 0, 0, 3, 0).unwrap()
+    };
+
+    //recoding converts the data file to the new type
+    dirfile.alter_entry("data", &Entry::new_raw("data", 1, GdTypes::Float64), true).unwrap();
+    assert_eq!(dirfile.native_type("data").unwrap(), GdTypes::Float64);
+    assert_eq!(read(&dirfile, "data"), vec![1.0, 2.0, 3.0]);
+    dirfile.alter_spec("cal LINCOM data 2 0", false).unwrap();
+    assert_eq!(read(&dirfile, "cal"), vec![2.0, 4.0, 6.0]);
+
+    dirfile.rename("data", "raw", &RenameOptions::new().update_references(true).rename_data(true)).unwrap();
+    assert!(path.join("raw").exists());
+    assert!(!path.join("data").exists());
+    assert!(dirfile.get_entry("data").is_err());
+    match dirfile.get_entry("cal").unwrap().field_type {
+        EntryType::Lincom(lincom) => assert_eq!(lincom.in_fields, vec![CString::new("raw").unwrap()]),
+        other => panic!("unexpected entry type {:?}", other),
+    }
+    assert_eq!(read(&dirfile, "cal"), vec![2.0, 4.0, 6.0]);
+
+    let sub = dirfile.include("sub", 0, &OpenOptions::new().create(true)).unwrap();
+    dirfile.move_entry("cal", sub, &RenameOptions::new()).unwrap();
+    assert_eq!(dirfile.fragment_index("cal").unwrap(), sub);
+
+    //"gain" is used by "inv", it can only go if its value is copied in
+    assert!(matches!(dirfile.delete("gain", &DeleteOptions::new()), Err(GdError::Delete(_))));
+    dirfile.delete("gain", &DeleteOptions::new().deref(true)).unwrap();
+    match dirfile.get_entry("inv").unwrap().field_type {
+        EntryType::Recip(recip) => assert_eq!(recip.dividend, Scalar::Literal(3.0)),
+        other => panic!("unexpected entry type {:?}", other),
+    }
+    assert!(dirfile.delete("raw", &DeleteOptions::new()).is_err());
+    dirfile.delete("raw", &DeleteOptions::new().force(true).data(true)).unwrap();
+    assert!(!path.join("raw").exists());
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_metafields(){
+    use super::*;
+    let file_name = "__testdirfile23__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("temp", 1, GdTypes::Float64)).unwrap();
+    dirfile.madd(&Entry::new_string("units"), "temp").unwrap();
+    dirfile.madd(&Entry::new_lincom("cal", vec!["temp"], vec![2.0], vec![1.0]), "temp").unwrap();
+    dirfile.add(&Entry::new_bit("temp/flag", "temp", 0, 1)).unwrap();
+    dirfile.put_string("temp/units", "K").unwrap();
+    //a metafield needs an existing parent
+    assert!(dirfile.madd(&Entry::new_string("units"), "pressure").is_err());
+
+    let entry = dirfile.get_entry("temp/cal").unwrap();
+    assert_eq!(entry.get_field_code(), "temp/cal");
+    assert_eq!(entry.parent(), Some("temp"));
+    assert_eq!(entry.name(), "cal");
+    assert_eq!(dirfile.get_entry("temp").unwrap().parent(), None);
+    assert_eq!(dirfile.get_string("temp/units").unwrap(), "K");
+
+    let mut metafields: Vec<String> = dirfile.metafields("temp", &EntryFilter::new()).unwrap().collect();
+    metafields.sort();
+    assert_eq!(metafields, vec!["cal", "flag", "units"]);
+    assert_eq!(dirfile.nmetafields("temp", &EntryFilter::new()).unwrap(), 3);
+    let strings: Vec<String> = dirfile.metafields("temp", &EntryFilter::new().kind(EntryKind::String)).unwrap().collect();
+    assert_eq!(strings, vec!["units"]);
+    //metafields are not listed with the top level fields
+    let field_codes: Vec<String> = dirfile.field_codes(&EntryFilter::new()).unwrap().collect();
+    assert!(!field_codes.iter().any(|field_code| field_code.contains('/')));
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_aliases(){
+    use super::*;
+    let file_name = "__testdirfile24__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("temp", 1, GdTypes::Float64)).unwrap();
+    dirfile.add(&Entry::new_raw("pressure", 1, GdTypes::Float64)).unwrap();
+    dirfile.add_alias("old_temp", FieldOrEntry::Field("temp".to_string())).unwrap();
+    dirfile.add_alias("T", FieldOrEntry::Field("temp".to_string())).unwrap();
+
+    assert_eq!(dirfile.naliases("temp").unwrap(), 3);
+    let mut aliases = dirfile.aliases("temp").unwrap();
+    aliases.sort();
+    assert_eq!(aliases, vec!["T", "old_temp", "temp"]);
+    assert_eq!(dirfile.alias_target("old_temp").unwrap(), "temp");
+    assert!(dirfile.alias_target("temp").is_err());
+    //lookups follow the alias
+    assert!(matches!(dirfile.get_entry("old_temp").unwrap().field_type, EntryType::Raw(_)));
+
+    let mut listed: Vec<String> = dirfile.field_codes(&EntryFilter::new().aliases()).unwrap().collect();
+    listed.sort();
+    assert_eq!(listed, vec!["T", "old_temp"]);
+    assert_eq!(dirfile.nentries(&EntryFilter::new()).unwrap(), 4);
+    assert_eq!(dirfile.nentries(&EntryFilter::new().include_aliases(false)).unwrap(), 2);
+
+    dirfile.retarget_alias("T", "pressure").unwrap();
+    assert_eq!(dirfile.alias_target("T").unwrap(), "pressure");
+    //a target GetData rejects leaves the alias as it was
+    assert!(matches!(dirfile.retarget_alias("T", "bad\x01code"), Err(GdError::BadCode(_))));
+    assert_eq!(dirfile.alias_target("T").unwrap(), "pressure");
+    //a hidden alias stays hidden, also when restored
+    dirfile.hide("T").unwrap();
+    assert!(dirfile.retarget_alias("T", "bad\x01code").is_err());
+    assert!(dirfile.hidden("T").unwrap());
+    dirfile.retarget_alias("T", "temp").unwrap();
+    assert!(dirfile.hidden("T").unwrap());
+    assert_eq!(dirfile.alias_target("T").unwrap(), "temp");
+    dirfile.retarget_alias("T", "pressure").unwrap();
+    dirfile.unhide("T").unwrap();
+    assert!(dirfile.retarget_alias("nosuchalias", "temp").is_err());
+    assert!(dirfile.delete_alias("temp").is_err());
+    dirfile.delete_alias("old_temp").unwrap();
+    assert_eq!(dirfile.naliases("temp").unwrap(), 1);
+    assert!(dirfile.get_entry("temp").is_ok());
+    dirfile.close().unwrap();
+
+    let dirfile = Dirfile::open(file_name).unwrap();
+    assert_eq!(dirfile.alias_target("T").unwrap(), "pressure");
+    assert!(dirfile.get_entry("old_temp").is_err());
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_hidden(){
+    use super::*;
+    let file_name = "__testdirfile25__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("data", 1, GdTypes::Float64)).unwrap();
+    let mut entry = Entry::new_lincom("step1", vec!["data"], vec![2.0], vec![0.0]);
+    entry.set_hidden(true);
+    assert!(entry.hidden());
+    dirfile.add(&entry).unwrap();
+    dirfile.add(&Entry::new_lincom("result", vec!["step1"], vec![1.0], vec![1.0])).unwrap();
+
+    assert!(dirfile.hidden("step1").unwrap());
+    assert!(!dirfile.hidden("data").unwrap());
+    assert!(dirfile.get_entry("step1").unwrap().hidden());
+    assert!(dirfile.hidden("nofield").is_err());
+    let listed: Vec<String> = dirfile.field_codes(&EntryFilter::new().vectors()).unwrap().collect();
+    assert!(!listed.contains(&"step1".to_string()));
+    let listed: Vec<String> = dirfile.field_codes(&EntryFilter::new().vectors().include_hidden(true)).unwrap().collect();
+    assert!(listed.contains(&"step1".to_string()));
+
+    dirfile.hide("data").unwrap();
+    dirfile.unhide("step1").unwrap();
+    assert!(!dirfile.get_entry("step1").unwrap().hidden());
+    dirfile.close().unwrap();
+
+    let dirfile = Dirfile::open(file_name).unwrap();
+    assert!(dirfile.hidden("data").unwrap());
+    assert!(!dirfile.hidden("step1").unwrap());
+    assert_eq!(dirfile.nentries(&EntryFilter::new().vectors()).unwrap(), 3);
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_spec(){
+    use super::*;
+    let file_name = "__testdirfile26__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    let sub = dirfile.include("sub", 0, &OpenOptions::new().create(true)).unwrap();
+    dirfile.add_spec("temp RAW UINT16 4", 0).unwrap();
+    dirfile.add_spec("temp_k LINCOM temp 0.01 273.15", sub).unwrap();
+    dirfile.madd_spec("units STRING K", "temp_k").unwrap();
+    assert_eq!(dirfile.spf("temp").unwrap(), 4);
+    assert_eq!(dirfile.fragment_index("temp_k").unwrap(), sub);
+    assert_eq!(dirfile.get_string("temp_k/units").unwrap(), "K");
+
+    match dirfile.add_spec("bad RAW NOTATYPE 1", 0) {
+        Err(GdError::Format(msg)) => assert!(msg.contains("bad RAW NOTATYPE 1")),
+        other => panic!("unexpected result {:?}", other.err()),
+    }
+    dirfile.close().unwrap();
+
+    //parsing on its own
+    let entry = Entry::from_spec("cal POLYNOM raw 1 2 gain").unwrap();
+    assert_eq!(entry.get_field_code(), "cal");
+    assert_eq!(entry.field_type, EntryType::Polynom(PolynomData {
+        in_field: CString::new("raw").unwrap(),
+        a: vec![1.0.into(), 2.0.into(), Scalar::reference("gain")],
+    }));
+    assert_eq!(Entry::from_spec("flag BIT raw 3").unwrap().field_type.kind(), EntryKind::Bit);
+    assert!(matches!(Entry::from_spec("cal LINCOM"), Err(GdError::Format(_))));
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_desync(){
+    use super::*;
+    let file_name = "__testdirfile27__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut writer = Dirfile::open(file_name).unwrap();
+    writer.add(&Entry::new_raw("data", 1, GdTypes::Float64)).unwrap();
+    writer.add_spec("cal LINCOM data 1 0", 0).unwrap();
+    writer.metaflush().unwrap();
+
+    let mut reader = OpenOptions::new().open(file_name).unwrap();
+    assert!(!reader.desync(&DesyncOptions::new()).unwrap());
+    let mut cached = vec![reader.get_entry("cal").unwrap()];
+
+    writer.add(&Entry::new_raw("new", 1, GdTypes::Float64)).unwrap();
+    writer.alter_spec("cal LINCOM data 2 1", false).unwrap();
+    writer.metaflush().unwrap();
+
+    //without reopening the change is only reported
+    assert!(reader.desync(&DesyncOptions::new()).unwrap());
+    assert!(reader.get_entry("new").is_err());
+    assert_eq!(reader.generation(), 0);
+
+    assert!(reader.desync(&DesyncOptions::new().reopen(true).pathcheck(true)).unwrap());
+    assert_eq!(reader.generation(), 1);
+    assert!(reader.get_entry("new").is_ok());
+    assert!(!reader.desync(&DesyncOptions::new().reopen(true)).unwrap());
+    assert_eq!(reader.generation(), 1);
+
+    reader.refresh_entries(&mut cached).unwrap();
+    match &cached[0].field_type {
+        EntryType::Lincom(lincom) => assert_eq!(lincom.m, vec![Scalar::Literal(2.0)]),
+        other => panic!("unexpected entry type {:?}", other),
+    }
+    writer.delete("cal", &DeleteOptions::new()).unwrap();
+    writer.metaflush().unwrap();
+    reader.desync(&DesyncOptions::new().reopen(true)).unwrap();
+    assert!(reader.refresh_entries(&mut cached).is_err());
+
+    reader.close().unwrap();
+    writer.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_field_handle(){
+    use super::*;
+    let file_name = "__testdirfile28__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("fast", 4, GdTypes::Int16)).unwrap();
+    dirfile.add(&Entry::new_raw("slow", 1, GdTypes::Float64)).unwrap();
+    dirfile.add(&Entry::new_multiply("prod", "fast", "fast")).unwrap();
+    dirfile.add(&Entry::new_const("gain", GdTypes::Float64)).unwrap();
+
+    //several handles can be used side by side
+    let fast = dirfile.field::<i16>("fast").unwrap();
+    let slow = dirfile.field::<f64>("slow").unwrap();
+    assert_eq!(fast.spf(), 4);
+    assert_eq!(fast.field_code(), "fast");
+    for frame in 0..3 {
+        assert_eq!(fast.write(&[frame as i16; 4]).unwrap(), 4);
+        assert_eq!(slow.write(&[frame as f64 / 2.0]).unwrap(), 1);
+    }
+    assert_eq!(fast.nframes().unwrap(), 3);
+    assert_eq!(slow.nframes().unwrap(), 3);
+    assert_eq!(fast.read(2..6).unwrap(), vec![0, 0, 1, 1]);
+    assert_eq!(slow.read(0..10).unwrap(), vec![0.0, 0.5, 1.0]);
+
+    let prod = dirfile.field::<f64>("prod").unwrap();
+    assert_eq!(prod.read(8..9).unwrap(), vec![4.0]);
+    assert!(matches!(prod.write(&[1.0]), Err(GdError::BadFieldType(_))));
+    assert!(dirfile.field::<f64>("gain").is_err());
+    assert!(matches!(dirfile.field::<f64>("nofield"), Err(GdError::BadCode(_))));
+
+    //the data went through the dirfile as usual
+    let data: Vec<i16> = dirfile.getdata(FieldOrEntry::Field("fast".to_string()), 2, 0, 1, 0).unwrap();
+    assert_eq!(data, vec![2; 4]);
+
+    //periodic flushes while the handles are held, a separate reader sees the data on disk
+    dirfile.metaflush().unwrap();
+    let fast = dirfile.field::<i16>("fast").unwrap();
+    let slow = dirfile.field::<f64>("slow").unwrap();
+    let reader = OpenOptions::new().open(file_name).unwrap();
+    for frame in 3..6 {
+        fast.write(&[frame as i16; 4]).unwrap();
+        slow.write(&[frame as f64 / 2.0]).unwrap();
+        if frame % 2 == 0 {
+            fast.flush().unwrap();
+            slow.flush().unwrap();
+        } else {
+            fast.sync().unwrap();
+            slow.sync().unwrap();
+        }
+        let on_disk: Vec<f64> = reader.getdata(FieldOrEntry::Field("slow".to_string()), frame, 0, 1, 0).unwrap();
+        assert_eq!(on_disk, vec![frame as f64 / 2.0]);
+    }
+    assert_eq!(fast.read(20..24).unwrap(), vec![5; 4]);
+
+    //data I/O through the dirfile mixes with the handles, it shares their I/O pointers
+    assert_eq!(dirfile.tell("slow").unwrap(), 6);
+    assert_eq!(dirfile.putdata(FieldOrEntry::Field("slow".to_string()), &[3.0f64]).unwrap(), 1);
+    dirfile.seek("fast", 1, 0, Whence::Set).unwrap();
+    fast.write(&[7; 4]).unwrap();
+    dirfile.flush().unwrap();
+    assert_eq!(slow.read(6..7).unwrap(), vec![3.0]);
+    let on_disk: Vec<i16> = reader.getdata(FieldOrEntry::Field("fast".to_string()), 1, 0, 1, 0).unwrap();
+    assert_eq!(on_disk, vec![7; 4]);
+    reader.close().unwrap();
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[test]
+fn test_highlevel_entry_mutation(){
+    use super::*;
+    let file_name = "__testdirfile29__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let mut dirfile = Dirfile::open(file_name).unwrap();
+    dirfile.add(&Entry::new_raw("raw", 1, GdTypes::Float64)).unwrap();
+
+    //changes made to an entry after it was built are what gets added
+    let mut entry = Entry::new_linterp("cal", "raw", "old.lut");
+    if let EntryType::Linterp(linterp_data) = &mut entry.field_type {
+        linterp_data.table = CString::new("new.lut").unwrap();
+    }
+    entry.field = CString::new("cal2").unwrap();
+    dirfile.add(&entry).unwrap();
+    assert!(dirfile.get_entry("cal").is_err());
+    match dirfile.get_entry("cal2").unwrap().field_type {
+        EntryType::Linterp(linterp_data) => assert!(linterp_data.table.to_str().unwrap().ends_with("new.lut")),
+        other => panic!("unexpected entry type {:?}", other),
+    }
+
+    let mut entry = dirfile.get_entry("raw").unwrap();
+    if let EntryType::Raw(raw_data) = &mut entry.field_type {
+        raw_data.spf = 4.into();
+    }
+    dirfile.alter_entry("raw", &entry, false).unwrap();
+    assert_eq!(dirfile.get_entry("raw").unwrap().field_type, EntryType::Raw(RawData { spf: 4.into(), gd_type: GdTypes::Float64 }));
+
+    //parameter vectors which don't fit the type are an error, not a panic
+    let mut entry = Entry::new_lincom("lin", vec!["raw"], vec![1.0], vec![0.0]);
+    if let EntryType::Lincom(lincom_data) = &mut entry.field_type {
+        lincom_data.in_fields.push(CString::new("raw").unwrap());
+    }
+    assert!(matches!(dirfile.add(&entry), Err(GdError::Argument(_))));
+    let mut entry = Entry::new_polynom("poly", "raw", vec![0.0, 1.0]);
+    if let EntryType::Polynom(polynom_data) = &mut entry.field_type {
+        polynom_data.a = vec![1.0.into(); 7];
+    }
+    assert!(matches!(dirfile.add(&entry), Err(GdError::Argument(_))));
+    dirfile.add(&Entry::new_polynom("poly", "raw", vec![0.0, 1.0])).unwrap();
+    assert!(matches!(dirfile.alter_entry("poly", &entry, false), Err(GdError::Argument(_))));
+    assert!(matches!(dirfile.madd(&entry, "raw"), Err(GdError::Argument(_))));
+    dirfile.close().unwrap();
+    std::fs::remove_dir_all(file_name).unwrap();
+}
+
+#[cfg(feature = "log")]
+#[test]
+fn test_highlevel_log(){
+    use super::*;
+    use std::sync::Mutex;
+    //collects every record, other tests may log at the same time so only look at ours
+    struct Capture(Mutex<Vec<(log::Level, String)>>);
+    impl log::Log for Capture {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+        fn log(&self, record: &log::Record) {
+            if record.target() == "rust_getdata" {
+                self.0.lock().unwrap().push((record.level(), record.args().to_string()));
+            }
+        }
+        fn flush(&self) {}
+    }
+    static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+    log::set_logger(&CAPTURE).unwrap();
+    log::set_max_level(log::LevelFilter::Debug);
+
+    let file_name = "__testdirfile30__";
+    let path = std::path::Path::new(file_name);
+    if path.exists() {
+        std::fs::remove_dir_all(file_name).unwrap();
+    }
+    let dirfile = Dirfile::open(file_name).unwrap();
+    assert!(matches!(dirfile.get_entry("nosuch"), Err(GdError::BadCode(_))));
+    //reading the error again neither clears nor reports it again
+    assert!(dirfile.get_error().is_some());
+    assert!(dirfile.get_error().is_some());
+    dirfile.close().unwrap();
+
+    let records: Vec<(log::Level, String)> = CAPTURE.0.lock().unwrap()
+        .iter()
+        .filter(|(_, msg)| msg.contains(file_name))
+        .cloned()
+        .collect();
+    assert!(records.contains(&(log::Level::Debug, format!("get_entry on {}, field_code = \"nosuch\"", file_name))));
+    assert!(records.iter().any(|(level, msg)| *level == log::Level::Debug && msg.starts_with(&format!("open on {}, options = ", file_name))));
+    let warnings: Vec<&String> = records.iter()
+        .filter(|(level, _)| *level == log::Level::Warn)
+        .map(|(_, msg)| msg)
+        .collect();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings.iter().all(|msg| msg.contains("nosuch") && msg.contains("BadCode")));
+    std::fs::remove_dir_all(file_name).unwrap();
+}
 
 #[test]
 fn test_highlevel_error(){
@@ -170,7 +1426,7 @@ fn test_highlevel_error(){
     let dirfile = dirfile.unwrap();
     unsafe{
     let fieldcode = CString::new("testfield").unwrap();
-    let _n_pts = gd_getdata(dirfile.dirfile.unwrap().as_ptr(), fieldcode.as_ptr(), 0, 0, 10, 0, gd_type_t_GD_FLOAT32, data.as_mut_ptr() as *mut c_void);
+    let _n_pts = gd_getdata(dirfile.dirfile.as_ptr(), fieldcode.as_ptr(), 0, 0, 10, 0, gd_type_t_GD_FLOAT32, data.as_mut_ptr() as *mut c_void);
     }
     let er = dirfile.get_error().unwrap();
     assert_eq!(er.message(), &"Field not found: testfield".to_string());